@@ -1,5 +1,9 @@
 use std::{fmt, fs::File, io::Read, path::Path, str::FromStr};
 
+mod point;
+
+pub use point::Point3;
+
 pub const SINGLELINE: &str = "\n";
 pub const MULTILINE: &str = "\n\n";
 
@@ -1,10 +1,23 @@
 use aoc::*;
+use clap::Parser;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
 use std::iter::Sum;
 use std::str::FromStr;
 
+#[derive(Parser, Debug)]
+#[clap(name = "d01", about = "Calorie Counting")]
+struct Opt {
+    /// how many of the best-stocked elves to rank
+    #[clap(short = 'k', long = "top", default_value = "3")]
+    top: usize,
+}
+
 #[derive(Debug)]
 struct Food {
-    calories: u32,
+    calories: u64,
 }
 
 impl FromStr for Food {
@@ -12,24 +25,26 @@ impl FromStr for Food {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
-            calories: s.parse().unwrap(),
+            calories: s.trim().parse().map_err(|_| ())?,
         })
     }
 }
 
-impl<'a> Sum<&'a Self> for Food {
-    fn sum<I>(iter: I) -> Self
+impl<'a> Sum<&'a Food> for Option<Food> {
+    fn sum<I>(mut iter: I) -> Self
     where
-        I: Iterator<Item = &'a Self>,
+        I: Iterator<Item = &'a Food>,
     {
-        iter.fold(Self { calories: 0 }, |a, b| Self {
-            calories: a.calories + b.calories,
+        iter.try_fold(Food { calories: 0 }, |a, b| {
+            Some(Food {
+                calories: a.calories.checked_add(b.calories)?,
+            })
         })
     }
 }
 
 impl Food {
-    fn get_calories(&self) -> u32 {
+    fn get_calories(&self) -> u64 {
         self.calories
     }
 }
@@ -44,30 +59,104 @@ impl FromStr for Elf {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
-            food: s.lines().map(|s| s.parse().unwrap()).collect(),
+            food: s.lines().map(|s| s.parse()).collect::<Result<_, _>>()?,
         })
     }
 }
 
 impl Elf {
-    pub fn get_total_calories(&self) -> u32 {
-        self.food.iter().sum::<Food>().get_calories()
+    /// The total calories carried, or `None` if it does not fit in a `u64`.
+    pub fn get_total_calories(&self) -> Option<u64> {
+        self.food
+            .iter()
+            .sum::<Option<Food>>()
+            .map(|f| f.get_calories())
     }
 }
 
-fn main() {
-    let input = read::<Elf>("d01/input.txt", MULTILINE);
+/// Reads elves one at a time from blank-line separated groups, so the input
+/// never has to be held in memory as a whole.
+struct Elves<B> {
+    lines: Lines<B>,
+}
+
+impl<B: BufRead> Elves<B> {
+    pub fn new(reader: B) -> Self {
+        Elves {
+            lines: reader.lines(),
+        }
+    }
+}
+
+impl<B: BufRead> Iterator for Elves<B> {
+    type Item = Result<Elf, ()>;
 
-    let mut all_calories = input
-        .iter()
-        .map(|elf| elf.get_total_calories())
-        .collect::<Vec<u32>>();
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut group = String::new();
+        for line in self.lines.by_ref() {
+            let Ok(line) = line else {
+                return Some(Err(()));
+            };
+            if line.trim().is_empty() {
+                if group.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            group.push_str(&line);
+            group.push('\n');
+        }
+        (!group.is_empty()).then(|| group.parse())
+    }
+}
+
+/// An elf's total calories together with its position in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Ranking {
+    calories: u64,
+    index: usize,
+}
 
-    all_calories.sort();
-    let max = *all_calories.iter().max().unwrap();
-    all_calories.drain(0..(all_calories.len() - 3));
+/// Keeps the `k` largest items of `iter` in a bounded min-heap and returns
+/// them largest first, in O(n log k) time and O(k) memory.
+fn top_k<T: Ord, I: IntoIterator<Item = T>>(iter: I, k: usize) -> Vec<T> {
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for item in iter {
+        heap.push(Reverse(item));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec().into_iter().map(|r| r.0).collect()
+}
+
+fn main() {
+    let opt = Opt::parse();
+    let elves = Elves::new(BufReader::new(
+        File::open("d01/input.txt").expect("no such file"),
+    ));
 
-    let sum = all_calories.iter().sum::<u32>();
+    // part 1 wants the best elf even when ranking none
+    let top = top_k(
+        elves.enumerate().map(|(index, elf)| Ranking {
+            calories: elf
+                .expect("expect valid calorie list")
+                .get_total_calories()
+                .expect("expect total calories to fit in u64"),
+            index,
+        }),
+        opt.top.max(1),
+    );
+    let best = top.first().map_or(0, |r| r.calories);
+    let top = &top[..opt.top.min(top.len())];
 
-    output!(max, sum)
+    output!(best, top.iter().map(|r| r.calories).sum::<u64>());
+    println!(
+        "Top {}: {}",
+        opt.top,
+        top.iter()
+            .map(|r| format!("elf {} ({})", r.index + 1, r.calories))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
 }
@@ -0,0 +1,522 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shape(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::Loss, Outcome::Draw, Outcome::Win];
+
+    fn score(&self) -> u32 {
+        match self {
+            Outcome::Loss => 0,
+            Outcome::Draw => 3,
+            Outcome::Win => 6,
+        }
+    }
+}
+
+/// A game of any odd number of shapes in which every shape beats exactly
+/// half of the others, described by a declarative beats-table.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    names: Vec<String>,
+    beats: Vec<Vec<bool>>,
+}
+
+impl Rules {
+    pub fn new(table: &[(&str, &[&str])]) -> Option<Self> {
+        let names: Vec<String> = table.iter().map(|(name, _)| name.to_string()).collect();
+        let index = |name: &str| names.iter().position(|n| n == name);
+
+        let mut beats = vec![vec![false; names.len()]; names.len()];
+        for (name, defeated) in table {
+            for other in defeated.iter() {
+                beats[index(name)?][index(other)?] = true;
+            }
+        }
+
+        let n = names.len();
+        let balanced = n % 2 == 1
+            && (0..n).all(|a| beats[a].iter().filter(|b| **b).count() == n / 2)
+            && (0..n).all(|a| (0..n).all(|b| a == b || beats[a][b] != beats[b][a]));
+        balanced.then_some(Rules { names, beats })
+    }
+
+    pub fn rock_paper_scissors() -> Self {
+        Rules::new(&[
+            ("Rock", &["Scissors"]),
+            ("Paper", &["Rock"]),
+            ("Scissors", &["Paper"]),
+        ])
+        .unwrap()
+    }
+
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        Rules::new(&[
+            ("Rock", &["Scissors", "Lizard"]),
+            ("Paper", &["Rock", "Spock"]),
+            ("Scissors", &["Paper", "Lizard"]),
+            ("Lizard", &["Spock", "Paper"]),
+            ("Spock", &["Scissors", "Rock"]),
+        ])
+        .unwrap()
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "rps" => Some(Rules::rock_paper_scissors()),
+            "rpsls" => Some(Rules::rock_paper_scissors_lizard_spock()),
+            _ => None,
+        }
+    }
+
+    pub fn shapes(&self) -> impl Iterator<Item = Shape> {
+        (0..self.names.len()).map(Shape)
+    }
+
+    pub fn name(&self, shape: Shape) -> &str {
+        &self.names[shape.0]
+    }
+
+    fn outcome(&self, own: Shape, other: Shape) -> Outcome {
+        if own == other {
+            Outcome::Draw
+        } else if self.beats[own.0][other.0] {
+            Outcome::Win
+        } else {
+            Outcome::Loss
+        }
+    }
+
+    /// The highest-scoring shape that reaches `outcome` against `opponent`.
+    fn respond_to(&self, opponent: Shape, outcome: Outcome) -> Shape {
+        self.shapes()
+            .filter(|s| self.outcome(*s, opponent) == outcome)
+            .max()
+            .unwrap()
+    }
+
+    /// Shapes are worth their position in the table, starting at 1.
+    fn score(&self, own: Shape, other: Shape) -> u32 {
+        own.0 as u32 + 1 + self.outcome(own, other).score()
+    }
+}
+
+/// Maps the letters used in a column of the strategy guide to their meaning.
+#[derive(Debug, Clone)]
+pub struct Letters<T>(HashMap<String, T>);
+
+impl<T: Copy> Letters<T> {
+    /// Pairs each letter with the value in the same position, or `None`
+    /// unless the letters are distinct and there is one for every value.
+    pub fn new<S: AsRef<str>>(letters: &[S], values: impl IntoIterator<Item = T>) -> Option<Self> {
+        let values: Vec<T> = values.into_iter().collect();
+        let map: HashMap<String, T> = letters
+            .iter()
+            .map(|l| l.as_ref().to_string())
+            .zip(values.iter().copied())
+            .collect();
+        (letters.len() == values.len() && map.len() == values.len()).then_some(Letters(map))
+    }
+
+    fn get(&self, letter: &str) -> Option<T> {
+        self.0.get(letter).copied()
+    }
+
+    /// Every way of giving the same letters distinct meanings from `values`.
+    fn reassignments(&self, values: &[T]) -> Vec<Letters<T>> {
+        let mut letters: Vec<&String> = self.0.keys().collect();
+        letters.sort();
+
+        arrangements(values, letters.len())
+            .into_iter()
+            .filter_map(|chosen| Letters::new(&letters, chosen))
+            .collect()
+    }
+}
+
+/// All ordered selections of `n` distinct items from `values`.
+fn arrangements<T: Copy>(values: &[T], n: usize) -> Vec<Vec<T>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+    (0..values.len())
+        .flat_map(|i| {
+            let mut rest = values.to_vec();
+            let first = rest.remove(i);
+            arrangements(&rest, n - 1).into_iter().map(move |mut a| {
+                a.insert(0, first);
+                a
+            })
+        })
+        .collect()
+}
+
+/// An interpretation of the second column of the strategy guide.
+pub trait Strategy: Sized {
+    const NAME: &'static str;
+
+    type Meaning: Copy;
+
+    fn mapping(&self) -> &Letters<Self::Meaning>;
+
+    fn respond(&self, rules: &Rules, opponent: Shape, letter: &str) -> Option<Shape>;
+
+    fn describe(&self, rules: &Rules, meaning: Self::Meaning) -> String;
+
+    /// The same interpretation with its letters assigned every other way.
+    fn alternatives(&self, rules: &Rules) -> Vec<Self>;
+
+    /// What each letter stands for under this interpretation.
+    fn letters(&self, rules: &Rules) -> BTreeMap<String, String> {
+        self.mapping()
+            .0
+            .iter()
+            .map(|(letter, meaning)| (letter.clone(), self.describe(rules, *meaning)))
+            .collect()
+    }
+}
+
+/// The second column names the shape to play.
+pub struct PlayShape(pub Letters<Shape>);
+
+impl Strategy for PlayShape {
+    const NAME: &'static str = "shape";
+
+    type Meaning = Shape;
+
+    fn mapping(&self) -> &Letters<Shape> {
+        &self.0
+    }
+
+    fn respond(&self, _: &Rules, _: Shape, letter: &str) -> Option<Shape> {
+        self.0.get(letter)
+    }
+
+    fn describe(&self, rules: &Rules, shape: Shape) -> String {
+        rules.name(shape).to_string()
+    }
+
+    fn alternatives(&self, rules: &Rules) -> Vec<Self> {
+        let shapes: Vec<Shape> = rules.shapes().collect();
+        self.0
+            .reassignments(&shapes)
+            .into_iter()
+            .map(PlayShape)
+            .collect()
+    }
+}
+
+/// The second column names the outcome the round needs to end in.
+pub struct ReachOutcome(pub Letters<Outcome>);
+
+impl Strategy for ReachOutcome {
+    const NAME: &'static str = "outcome";
+
+    type Meaning = Outcome;
+
+    fn mapping(&self) -> &Letters<Outcome> {
+        &self.0
+    }
+
+    fn respond(&self, rules: &Rules, opponent: Shape, letter: &str) -> Option<Shape> {
+        Some(rules.respond_to(opponent, self.0.get(letter)?))
+    }
+
+    fn describe(&self, _: &Rules, outcome: Outcome) -> String {
+        format!("{outcome:?}")
+    }
+
+    fn alternatives(&self, _: &Rules) -> Vec<Self> {
+        self.0
+            .reassignments(&Outcome::ALL)
+            .into_iter()
+            .map(ReachOutcome)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Round(String, String);
+
+impl FromStr for Round {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut temp = s.split_whitespace();
+
+        Ok(Self(
+            temp.next().ok_or(())?.to_string(),
+            temp.next().ok_or(())?.to_string(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundResult {
+    pub opponent: String,
+    pub own: String,
+    pub outcome: Outcome,
+    pub points: u32,
+}
+
+/// The outcome of playing the whole guide under one interpretation.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    pub total: u32,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub points_by_shape: BTreeMap<String, u32>,
+    pub rounds: Vec<RoundResult>,
+}
+
+/// A total score together with the letter assignment that achieves it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Bound {
+    pub score: u32,
+    pub letters: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Analysis {
+    pub interpretation: &'static str,
+    pub report: Report,
+    pub best: Bound,
+    pub worst: Bound,
+}
+
+fn join<V: fmt::Display>(map: &BTreeMap<String, V>) -> String {
+    map.iter()
+        .map(|(k, v)| format!("{k} {v}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Report {
+            total,
+            wins,
+            draws,
+            losses,
+            points_by_shape,
+            ..
+        } = &self.report;
+        writeln!(f, "By {}: {total} points", self.interpretation)?;
+        writeln!(f, "  {wins} wins, {draws} draws, {losses} losses")?;
+        writeln!(f, "  points by shape: {}", join(points_by_shape))?;
+        writeln!(
+            f,
+            "  best {} ({})",
+            self.best.score,
+            join(&self.best.letters)
+        )?;
+        write!(
+            f,
+            "  worst {} ({})",
+            self.worst.score,
+            join(&self.worst.letters)
+        )
+    }
+}
+
+pub struct Tournament {
+    rules: Rules,
+    opponent: Letters<Shape>,
+}
+
+impl Tournament {
+    /// Sets up the game, or `None` unless the opponent's letters name every
+    /// shape exactly once.
+    pub fn new<S: AsRef<str>>(rules: Rules, opponent: &[S]) -> Option<Self> {
+        Some(Tournament {
+            opponent: Letters::new(opponent, rules.shapes())?,
+            rules,
+        })
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn score<S: Strategy>(&self, rounds: &[Round], strategy: &S) -> Option<u32> {
+        rounds
+            .iter()
+            .map(|Round(opponent, response)| {
+                let opponent = self.opponent.get(opponent)?;
+                let own = strategy.respond(&self.rules, opponent, response)?;
+                Some(self.rules.score(own, opponent))
+            })
+            .sum()
+    }
+
+    pub fn report<S: Strategy>(&self, rounds: &[Round], strategy: &S) -> Option<Report> {
+        let mut report = Report::default();
+        for Round(opponent, response) in rounds {
+            let opponent = self.opponent.get(opponent)?;
+            let own = strategy.respond(&self.rules, opponent, response)?;
+            let outcome = self.rules.outcome(own, opponent);
+            let points = self.rules.score(own, opponent);
+
+            report.total += points;
+            match outcome {
+                Outcome::Win => report.wins += 1,
+                Outcome::Draw => report.draws += 1,
+                Outcome::Loss => report.losses += 1,
+            }
+            *report
+                .points_by_shape
+                .entry(self.rules.name(own).to_string())
+                .or_default() += points;
+            report.rounds.push(RoundResult {
+                opponent: self.rules.name(opponent).to_string(),
+                own: self.rules.name(own).to_string(),
+                outcome,
+                points,
+            });
+        }
+        Some(report)
+    }
+
+    /// Reports on the guide as given and scores every other assignment of the
+    /// same letters to find the best and worst the guide could have meant.
+    pub fn analyse<S: Strategy>(&self, rounds: &[Round], strategy: &S) -> Option<Analysis> {
+        let bound = |s: &S| {
+            Some(Bound {
+                score: self.score(rounds, s)?,
+                letters: s.letters(&self.rules),
+            })
+        };
+        let bounds = strategy
+            .alternatives(&self.rules)
+            .iter()
+            .map(bound)
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Analysis {
+            interpretation: S::NAME,
+            report: self.report(rounds, strategy)?,
+            best: bounds.iter().max_by_key(|b| b.score)?.clone(),
+            worst: bounds.iter().min_by_key(|b| b.score)?.clone(),
+        })
+    }
+}
+
+/// Analyses a strategy guide under both interpretations of the second column,
+/// using the standard rules and letters.
+pub fn analyse(guide: &str) -> Option<Vec<Analysis>> {
+    let rounds = guide
+        .lines()
+        .map(|l| l.parse())
+        .collect::<Result<Vec<Round>, _>>()
+        .ok()?;
+    let tournament = Tournament::new(Rules::rock_paper_scissors(), &["A", "B", "C"])?;
+    let letters = ["X", "Y", "Z"];
+
+    Some(vec![
+        tournament.analyse(
+            &rounds,
+            &PlayShape(Letters::new(&letters, tournament.rules().shapes())?),
+        )?,
+        tournament.analyse(
+            &rounds,
+            &ReachOutcome(Letters::new(&letters, Outcome::ALL)?),
+        )?,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rules: &Rules) -> Vec<Vec<Outcome>> {
+        rules
+            .shapes()
+            .map(|own| {
+                rules
+                    .shapes()
+                    .map(|other| rules.outcome(own, other))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rock_paper_scissors() {
+        use Outcome::*;
+        assert_eq!(
+            table(&Rules::rock_paper_scissors()),
+            vec![
+                vec![Draw, Loss, Win],
+                vec![Win, Draw, Loss],
+                vec![Loss, Win, Draw],
+            ]
+        );
+    }
+
+    #[test]
+    fn rock_paper_scissors_lizard_spock() {
+        use Outcome::*;
+        assert_eq!(
+            table(&Rules::rock_paper_scissors_lizard_spock()),
+            vec![
+                vec![Draw, Loss, Win, Win, Loss],
+                vec![Win, Draw, Loss, Loss, Win],
+                vec![Loss, Win, Draw, Win, Loss],
+                vec![Loss, Win, Loss, Draw, Win],
+                vec![Win, Loss, Win, Loss, Draw],
+            ]
+        );
+    }
+
+    #[test]
+    fn unbalanced_tables_are_rejected() {
+        // Rock beats everything
+        assert!(Rules::new(&[
+            ("Rock", &["Paper", "Scissors"]),
+            ("Paper", &["Scissors"]),
+            ("Scissors", &[]),
+        ])
+        .is_none());
+        // an even number of shapes cannot be balanced
+        assert!(Rules::new(&[("Rock", &["Paper"]), ("Paper", &[])]).is_none());
+        // both beat each other
+        assert!(Rules::new(&[
+            ("Rock", &["Paper"]),
+            ("Paper", &["Rock"]),
+            ("Scissors", &["Paper"]),
+        ])
+        .is_none());
+        assert!(Rules::new(&[("Rock", &["Stone"])]).is_none());
+    }
+
+    #[test]
+    fn letters_must_match_values() {
+        let shapes = || Rules::rock_paper_scissors().shapes();
+        assert!(Letters::new(&["X", "Y", "Z"], shapes()).is_some());
+        assert!(Letters::new(&["X", "Y"], shapes()).is_none());
+        assert!(Letters::new(&["X", "Y", "Z", "W"], shapes()).is_none());
+        assert!(Letters::new(&["X", "Y", "Y"], shapes()).is_none());
+        assert!(
+            Tournament::new(Rules::rock_paper_scissors_lizard_spock(), &["A", "B", "C"]).is_none()
+        );
+    }
+
+    #[test]
+    fn example() {
+        let analyses = analyse("A Y\nB X\nC Z").unwrap();
+        assert_eq!(analyses[0].report.total, 15);
+        assert_eq!(analyses[1].report.total, 12);
+    }
+}
@@ -1,110 +1,201 @@
 use aoc::*;
+use clap::builder::RangedU64ValueParser;
+use clap::Parser;
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
 use std::str::FromStr;
 
-#[derive(Debug)]
+#[derive(Parser, Debug)]
+#[clap(name = "d03", about = "Rucksack Reorganization")]
+struct Opt {
+    /// how many consecutive rucksacks make up a group
+    #[clap(short, long, default_value = "3")]
+    #[clap(value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    group_size: usize,
+}
+
+/// An item type, identified by its priority in `1..=52`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Item(u8);
 
-#[derive(Debug, Clone)]
-struct Rucksack {
-    compartments: [Vec<u8>; 2],
+impl TryFrom<char> for Item {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'a'..='z' => Ok(Item(c as u8 - b'a' + 1)),
+            'A'..='Z' => Ok(Item(c as u8 - b'A' + 27)),
+            _ => Err(()),
+        }
+    }
 }
 
-impl FromStr for Rucksack {
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self.0 {
+            p @ 1..=26 => b'a' + p - 1,
+            p => b'A' + p - 27,
+        };
+        write!(f, "{}", c as char)
+    }
+}
+
+/// A set of item types, with bit `p` set when the item of priority `p` is
+/// present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Items(u64);
+
+impl Items {
+    const ALL: Items = Items(((1 << 52) - 1) << 1);
+
+    pub fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn priority(self) -> u32 {
+        self.into_iter().map(|i| i.0 as u32).sum()
+    }
+}
+
+impl FromIterator<Item> for Items {
+    fn from_iter<I: IntoIterator<Item = Item>>(iter: I) -> Self {
+        Items(iter.into_iter().fold(0, |mask, i| mask | 1 << i.0))
+    }
+}
+
+impl FromStr for Items {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let decode = |b| match b {
-            'a'..='z' => b as u8 - 96,
-            'A'..='Z' => b as u8 - 38,
-            _ => unreachable!(),
-        };
+        s.chars().map(Item::try_from).collect()
+    }
+}
 
-        let (a, b) = s.split_at(s.len() / 2);
-        Ok(Self {
-            compartments: [
-                a.chars().map(decode).collect(),
-                b.chars().map(decode).collect(),
-            ],
-        })
+impl BitAnd for Items {
+    type Output = Items;
+
+    fn bitand(self, other: Items) -> Items {
+        Items(self.0 & other.0)
+    }
+}
+
+impl BitOr for Items {
+    type Output = Items;
+
+    fn bitor(self, other: Items) -> Items {
+        Items(self.0 | other.0)
     }
 }
 
-impl<'a> IntoIterator for &'a Rucksack {
-    type Item = &'a u8;
-    type IntoIter = RucksackIter<'a>;
+impl IntoIterator for Items {
+    type Item = Item;
+    type IntoIter = ItemsIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        RucksackIter {
-            items: self
-                .compartments
-                .iter()
-                .flat_map(|c| c.iter().collect::<Vec<&u8>>())
-                .collect(),
-            index: 0,
-        }
+        ItemsIter { mask: self.0 }
     }
 }
 
-struct RucksackIter<'a> {
-    items: Vec<&'a u8>,
-    index: usize,
+/// Walks the set bits of a mask, lowest priority first.
+struct ItemsIter {
+    mask: u64,
 }
 
-impl<'a> Iterator for RucksackIter<'a> {
-    type Item = &'a u8;
+impl Iterator for ItemsIter {
+    type Item = Item;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.items.len() {
+        if self.mask == 0 {
             return None;
         }
-        self.index += 1;
-        Some(self.items[self.index - 1])
+        let priority = self.mask.trailing_zeros() as u8;
+        self.mask &= self.mask - 1;
+        Some(Item(priority))
     }
 }
 
-impl Rucksack {
-    fn intersect(&self) -> u8 {
-        for a in self.compartments[0].iter() {
-            for b in self.compartments[1].iter() {
-                if *a == *b {
-                    return *a;
-                }
-            }
+impl fmt::Display for Items {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return write!(f, "none");
         }
-        0
+        self.into_iter().try_for_each(|i| write!(f, "{i}"))
     }
 }
 
-#[derive(Debug)]
-struct Group {
-    rucksacks: Vec<Rucksack>,
-}
-
-impl Group {
-    fn intersect(&self) -> u8 {
-        for a in self.rucksacks[0].into_iter() {
-            for b in self.rucksacks[1].into_iter() {
-                if a == b {
-                    for c in self.rucksacks[2].into_iter() {
-                        if a == c {
-                            return *a;
-                        }
-                    }
-                }
-            }
+#[derive(Debug, Clone, Copy)]
+struct Rucksack {
+    compartments: [Items; 2],
+}
+
+impl FromStr for Rucksack {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.len().is_multiple_of(2) || !s.is_ascii() {
+            return Err(());
         }
-        0
+        let (a, b) = s.split_at(s.len() / 2);
+        Ok(Self {
+            compartments: [a.parse()?, b.parse()?],
+        })
+    }
+}
+
+impl Rucksack {
+    /// The item types packed in both compartments.
+    pub fn common(&self) -> Items {
+        self.compartments[0] & self.compartments[1]
+    }
+
+    /// Every item type in the rucksack.
+    pub fn items(&self) -> Items {
+        self.compartments[0] | self.compartments[1]
+    }
+}
+
+#[derive(Debug)]
+struct Group<'a> {
+    rucksacks: &'a [Rucksack],
+}
+
+impl Group<'_> {
+    /// The item types carried by every elf in the group.
+    pub fn common(&self) -> Items {
+        self.rucksacks
+            .iter()
+            .fold(Items::ALL, |acc, r| acc & r.items())
+    }
+}
+
+/// Prints every set that does not hold exactly one item, since the puzzle
+/// promises one and the answers are only meaningful when that holds.
+fn report(kind: &str, sets: impl Iterator<Item = (usize, Items)>) {
+    for (index, items) in sets.filter(|(_, items)| items.len() != 1) {
+        println!("{kind} {}: common items {items}", index + 1);
     }
 }
 
 fn main() {
+    let opt = Opt::parse();
     let input = read::<Rucksack>("d03/input.txt", SINGLELINE);
-    let input2 = input.chunks_exact(3).map(|c| Group {
-        rucksacks: c.to_vec(),
-    });
+    let chunks = input.chunks_exact(opt.group_size);
+    let leftover = chunks.remainder().len();
+    let groups: Vec<Group> = chunks.map(|rucksacks| Group { rucksacks }).collect();
+
+    if leftover > 0 {
+        println!(
+            "warning: the last {leftover} of {} rucksacks do not fill a group of {}",
+            input.len(),
+            opt.group_size
+        );
+    }
+
+    report("Rucksack", input.iter().map(Rucksack::common).enumerate());
+    report("Group", groups.iter().map(Group::common).enumerate());
 
     output!(
-        input.iter().map(|r| r.intersect() as u32).sum::<u32>(),
-        input2.map(|g| g.intersect() as u32).sum::<u32>()
+        input.iter().map(|r| r.common().priority()).sum::<u32>(),
+        groups.iter().map(|g| g.common().priority()).sum::<u32>()
     );
 }
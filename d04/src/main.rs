@@ -1,65 +1,213 @@
 use aoc::*;
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::str::FromStr;
 
-#[derive(Debug, Clone)]
-struct Range((u8, u8));
+#[derive(Parser, Debug)]
+#[clap(name = "d04", about = "Camp Cleanup")]
+struct Opt {
+    /// print the coverage histogram, the redundant pairs and the minimal cover
+    #[clap(short, long)]
+    list: bool,
+}
+
+/// An inclusive range of section IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Interval {
+    start: u32,
+    end: u32,
+}
 
-impl FromStr for Range {
+impl FromStr for Interval {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Range(
-            s.split_once('-')
-                .map(|(start, end)| (start.parse().unwrap(), end.parse().unwrap()))
-                .unwrap(),
-        ))
+        let (start, end) = s.split_once('-').ok_or(())?;
+        let interval = Interval {
+            start: start.trim().parse().map_err(|_| ())?,
+            end: end.trim().parse().map_err(|_| ())?,
+        };
+        (interval.start <= interval.end)
+            .then_some(interval)
+            .ok_or(())
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl Interval {
+    fn len(&self) -> u32 {
+        self.end - self.start + 1
+    }
+
+    fn contains(&self, other: &Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
     }
 }
 
 #[derive(Debug, Clone)]
-struct RangePair((Range, Range));
+struct Pair {
+    first: Interval,
+    second: Interval,
+}
 
-impl FromStr for RangePair {
+impl FromStr for Pair {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(RangePair(
-            s.split_once(',')
-                .map(|(start, end)| (start.parse().unwrap(), end.parse().unwrap()))
-                .unwrap(),
-        ))
+        let (first, second) = s.split_once(',').ok_or(())?;
+        Ok(Pair {
+            first: first.parse()?,
+            second: second.parse()?,
+        })
+    }
+}
+
+impl Pair {
+    /// One elf's sections are all cleaned by the other elf anyway.
+    fn is_redundant(&self) -> bool {
+        self.first.contains(&self.second) || self.second.contains(&self.first)
+    }
+
+    fn overlaps(&self) -> bool {
+        self.first.overlaps(&self.second)
+    }
+}
+
+/// An assignment together with the elf it was given to, counting elves from
+/// zero in input order.
+#[derive(Debug, Clone, Copy)]
+struct Assignment {
+    elf: usize,
+    sections: Interval,
+}
+
+fn assignments(pairs: &[Pair]) -> impl Iterator<Item = Assignment> + '_ {
+    pairs
+        .iter()
+        .flat_map(|p| [p.first, p.second])
+        .enumerate()
+        .map(|(elf, sections)| Assignment { elf, sections })
+}
+
+/// Sweeps over the assignment boundaries and returns the maximal runs of
+/// sections cleaned by the same number of elves, skipping uncovered gaps.
+fn coverage(assignments: impl Iterator<Item = Assignment>) -> Vec<(Interval, usize)> {
+    let mut events: BTreeMap<u32, isize> = BTreeMap::new();
+    for a in assignments {
+        *events.entry(a.sections.start).or_default() += 1;
+        *events.entry(a.sections.end + 1).or_default() -= 1;
     }
+
+    let mut runs = vec![];
+    let mut depth = 0;
+    let mut events = events.into_iter().peekable();
+    while let Some((start, delta)) = events.next() {
+        depth += delta;
+        match events.peek() {
+            Some(&(next, _)) if depth > 0 => runs.push((
+                Interval {
+                    start,
+                    end: next - 1,
+                },
+                depth as usize,
+            )),
+            _ => {}
+        }
+    }
+    runs
 }
 
-impl RangePair {
-    fn is_fully_overlapped(&self) -> bool {
-        self.0 .0 .0 .0 <= self.0 .1 .0 .0 && self.0 .0 .0 .1 >= self.0 .1 .0 .1
-            || self.0 .1 .0 .0 <= self.0 .0 .0 .0 && self.0 .1 .0 .1 >= self.0 .0 .0 .1
+/// Counts how many sections are cleaned by exactly `n` elves, keyed by `n`.
+fn histogram(runs: &[(Interval, usize)]) -> BTreeMap<usize, u32> {
+    let mut histogram = BTreeMap::new();
+    for (interval, depth) in runs {
+        *histogram.entry(*depth).or_default() += interval.len();
     }
+    histogram
+}
+
+/// Picks as few assignments as possible whose union is the same set of
+/// sections as all of them together: within each stretch of sections, keep
+/// taking the assignment that starts inside what is covered so far and
+/// reaches furthest.
+fn minimal_cover(assignments: impl Iterator<Item = Assignment>) -> Vec<Assignment> {
+    let mut sorted: Vec<Assignment> = assignments.collect();
+    sorted.sort_unstable_by_key(|a| a.sections);
 
-    fn is_partially_overlapped(&self) -> bool {
-        for first in self.0 .0 .0 .0..=self.0 .0 .0 .1 {
-            for last in self.0 .1 .0 .0..=self.0 .1 .0 .1 {
-                if first == last {
-                    return true;
-                }
+    let mut cover: Vec<Assignment> = vec![];
+    let mut i = 0;
+    while i < sorted.len() {
+        // the first section not yet covered, restarting after any gap
+        let next = match cover.last() {
+            Some(a) if sorted[i].sections.start <= a.sections.end + 1 => a.sections.end + 1,
+            _ => sorted[i].sections.start,
+        };
+        let mut best = sorted[i];
+        while i < sorted.len() && sorted[i].sections.start <= next {
+            if sorted[i].sections.end > best.sections.end {
+                best = sorted[i];
             }
+            i += 1;
+        }
+        if best.sections.end >= next {
+            cover.push(best);
         }
-        false
     }
+    cover
 }
 
 fn main() {
-    let input = read::<RangePair>("d04/input.txt", SINGLELINE);
+    let opt = Opt::parse();
+    let input = read::<Pair>("d04/input.txt", SINGLELINE);
+
+    let redundant: Vec<(usize, &Pair)> = input
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.is_redundant())
+        .collect();
+    let runs = coverage(assignments(&input));
+    let cover = minimal_cover(assignments(&input));
 
     output!(
-        input
-            .iter()
-            .filter_map(|c| c.is_fully_overlapped().then_some(true))
-            .count(),
-        input
-            .iter()
-            .filter_map(|c| c.is_partially_overlapped().then_some(true))
-            .count()
+        redundant.len(),
+        input.iter().filter(|p| p.overlaps()).count()
+    );
+
+    println!(
+        "Coverage: {} sections, at most {} elves on one section",
+        runs.iter().map(|(i, _)| i.len()).sum::<u32>(),
+        runs.iter().map(|(_, depth)| depth).max().unwrap_or(&0)
+    );
+    println!(
+        "Minimal cover: {} of {} assignments",
+        cover.len(),
+        input.len() * 2
     );
+
+    if opt.list {
+        for (depth, sections) in histogram(&runs) {
+            println!("{sections:>4} sections cleaned by {depth} elves");
+        }
+        for (line, pair) in redundant {
+            println!(
+                "Redundant pair {}: {},{}",
+                line + 1,
+                pair.first,
+                pair.second
+            );
+        }
+        for a in cover {
+            println!("Cover: elf {} ({})", a.elf + 1, a.sections);
+        }
+    }
 }
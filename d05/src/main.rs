@@ -1,7 +1,40 @@
 use aoc::*;
+use clap::Parser;
+use std::fmt;
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Parser, Debug)]
+#[clap(name = "d05", about = "Supply Stacks")]
+struct Opt {
+    /// further crane models to run, by name: `9000`, `9001`, `limited:N` or
+    /// `fixed:N`
+    #[clap(short, long, value_delimiter = ',')]
+    cranes: Vec<String>,
+
+    /// draw the stacks after every procedure carried out by this crane model
+    #[clap(long)]
+    replay: Option<String>,
+
+    /// with `--replay`, undo this many procedures at the end and draw the
+    /// stacks again
+    #[clap(long, default_value = "0", requires = "replay")]
+    rewind: usize,
+
+    /// report every procedure this crane model could not carry out
+    #[clap(long)]
+    validate: Option<String>,
+
+    /// print the input with the stacks as this crane model leaves them
+    #[clap(long)]
+    show: Option<String>,
+
+    /// stop with an error at the first impossible procedure instead of
+    /// skipping it with a warning
+    #[clap(long)]
+    strict: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 struct Stack {
     crates: Vec<char>,
 }
@@ -16,124 +49,729 @@ impl FromIterator<char> for Stack {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+impl Stack {
+    /// Takes the top `n` crates off, keeping their order, or nothing if the
+    /// stack is not that tall.
+    fn lift(&mut self, n: usize) -> Option<Vec<char>> {
+        let rest = self.crates.len().checked_sub(n)?;
+        Some(self.crates.split_off(rest))
+    }
+
+    fn place(&mut self, mut crates: Vec<char>) {
+        self.crates.append(&mut crates);
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 struct Procedure {
-    n: u8,
+    n: usize,
     from: usize,
     to: usize,
 }
 
+impl fmt::Display for Procedure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.n, self.from, self.to)
+    }
+}
+
 impl FromStr for Procedure {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let values: Vec<&str> = s.split_whitespace().collect();
+        let [_, n, _, from, _, to] = values[..] else {
+            return Err(());
+        };
 
         Ok(Procedure {
-            n: values[1].parse().unwrap(),
-            from: values[3].parse().unwrap(),
-            to: values[5].parse().unwrap(),
+            n: n.parse().map_err(|_| ())?,
+            from: from.parse().map_err(|_| ())?,
+            to: to.parse().map_err(|_| ())?,
         })
     }
 }
 
-#[derive(Debug)]
-enum CrateMover {
-    M9000(Setup),
-    M9001(Setup),
+impl Procedure {
+    /// The lifts `crane` would make to carry out this procedure on `stacks`,
+    /// or why it cannot be done.
+    fn lifts(&self, stacks: &[Stack], crane: &dyn Crane) -> Result<Vec<Lift>, Problem> {
+        for stack in [self.from, self.to] {
+            if !(1..=stacks.len()).contains(&stack) {
+                return Err(Problem::NoSuchStack(stack));
+            }
+        }
+        let available = stacks[self.from - 1].crates.len();
+        if available < self.n {
+            return Err(Problem::TooFewCrates {
+                stack: self.from,
+                available,
+            });
+        }
+        let lifts = crane.lifts(self.n).ok_or(Problem::Unliftable)?;
+        Ok(lifts
+            .into_iter()
+            .map(|n| Lift {
+                n,
+                from: self.from - 1,
+                to: self.to - 1,
+            })
+            .collect())
+    }
+}
+
+/// Why a procedure cannot be carried out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Problem {
+    NoSuchStack(usize),
+    TooFewCrates { stack: usize, available: usize },
+    Unliftable,
 }
 
-impl CrateMover {
-    fn setup(&self) -> Setup {
-        match self {
-            CrateMover::M9000(setup) => setup.clone(),
-            CrateMover::M9001(setup) => setup.clone(),
+/// An impossible procedure and the input line it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Violation {
+    line: usize,
+    procedure: Procedure,
+    problem: Problem,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: ", self.line, self.procedure)?;
+        match self.problem {
+            Problem::NoSuchStack(stack) => write!(f, "there is no stack {stack}"),
+            Problem::TooFewCrates { stack, available } => {
+                write!(f, "stack {stack} only holds {available} crates")
+            }
+            Problem::Unliftable => write!(f, "the crane cannot move that many crates"),
         }
     }
+}
 
-    fn commit_procedures(&mut self) {
-        match self {
-            CrateMover::M9000(setup) => {
-                for Procedure { n, from, to } in setup.procedures.iter() {
-                    for _ in 0..*n {
-                        if let Some(c) = setup.stacks[*from - 1].crates.pop() {
-                            setup.stacks[*to - 1].crates.push(c);
-                        }
-                    }
-                }
-            }
-            CrateMover::M9001(setup) => {
-                for Procedure { n, from, to } in setup.procedures.iter() {
-                    let mut temp = vec![];
-                    for _ in 0..*n {
-                        if let Some(c) = setup.stacks[*from - 1].crates.pop() {
-                            temp.push(c);
-                        }
-                    }
-                    temp.reverse();
-                    setup.stacks[*to - 1].crates.append(&mut temp);
+/// How to treat impossible procedures: `Strict` stops at the first one,
+/// `Lenient` skips it and records a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Strict,
+    Lenient,
+}
+
+/// A crane model, described by how it splits moving some crates into lifts.
+trait Crane {
+    fn name(&self) -> String;
+
+    /// The sizes of the lifts used to move `n` crates, each lift keeping the
+    /// order of the crates it carries, or `None` if this crane cannot move
+    /// exactly `n` crates.
+    fn lifts(&self, n: usize) -> Option<Vec<usize>>;
+}
+
+/// Moves crates one at a time.
+struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        "CrateMover 9000".to_string()
+    }
+
+    fn lifts(&self, n: usize) -> Option<Vec<usize>> {
+        Some(vec![1; n])
+    }
+}
+
+/// Moves any number of crates at once.
+struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        "CrateMover 9001".to_string()
+    }
+
+    fn lifts(&self, n: usize) -> Option<Vec<usize>> {
+        Some(vec![n])
+    }
+}
+
+/// Moves up to `capacity` crates at once, lifting as many as it can each time.
+struct Limited {
+    capacity: usize,
+}
+
+impl Crane for Limited {
+    fn name(&self) -> String {
+        format!("Limited crane ({} crates)", self.capacity)
+    }
+
+    fn lifts(&self, n: usize) -> Option<Vec<usize>> {
+        let mut lifts = vec![self.capacity; n / self.capacity];
+        let rest = n % self.capacity;
+        if rest > 0 {
+            lifts.push(rest);
+        }
+        Some(lifts)
+    }
+}
+
+/// Can only ever lift exactly `size` crates at once.
+struct Fixed {
+    size: usize,
+}
+
+impl Crane for Fixed {
+    fn name(&self) -> String {
+        format!("Fixed crane ({} crates)", self.size)
+    }
+
+    fn lifts(&self, n: usize) -> Option<Vec<usize>> {
+        n.is_multiple_of(self.size)
+            .then(|| vec![self.size; n / self.size])
+    }
+}
+
+/// Looks a crane up by name: `9000`, `9001`, `limited:N` or `fixed:N`.
+fn crane(name: &str) -> Option<Box<dyn Crane>> {
+    let (model, size) = match name.split_once(':') {
+        Some((model, size)) => (model, size.parse().ok().filter(|&s| s > 0)),
+        None => (name, None),
+    };
+    match (model, size) {
+        ("9000", None) => Some(Box::new(CrateMover9000)),
+        ("9001", None) => Some(Box::new(CrateMover9001)),
+        ("limited", Some(capacity)) => Some(Box::new(Limited { capacity })),
+        ("fixed", Some(size)) => Some(Box::new(Fixed { size })),
+        _ => None,
+    }
+}
+
+/// Crates carried together in one go, between zero-based stack indices.
+#[derive(Debug, Clone, Copy)]
+struct Lift {
+    n: usize,
+    from: usize,
+    to: usize,
+}
+
+impl Lift {
+    /// The lift that puts the crates back where they came from.
+    fn inverse(self) -> Lift {
+        Lift {
+            n: self.n,
+            from: self.to,
+            to: self.from,
+        }
+    }
+
+    /// Makes the lift, or leaves the stacks alone if it is impossible.
+    fn apply(self, stacks: &mut [Stack]) {
+        if let Some(crates) = stacks[self.from].lift(self.n) {
+            stacks[self.to].place(crates);
+        }
+    }
+}
+
+/// Draws the stacks the way the puzzle does, one column per stack with the
+/// stack numbers underneath. The columns only line up for up to nine stacks.
+fn render(stacks: &[Stack]) -> String {
+    let height = stacks.iter().map(|s| s.crates.len()).max().unwrap_or(0);
+    let mut lines: Vec<String> = (0..height)
+        .rev()
+        .map(|row| {
+            stacks
+                .iter()
+                .map(|s| match s.crates.get(row) {
+                    Some(c) => format!("[{c}]"),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    lines.push(
+        (1..=stacks.len())
+            .map(|i| format!(" {i} "))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    lines.join("\n")
+}
+
+/// Steps through the procedures of a setup with some crane, remembering the
+/// lifts made for each procedure so that steps can be undone.
+struct Replay<'a> {
+    crane: &'a dyn Crane,
+    mode: Mode,
+    procedures: &'a [Procedure],
+    first_line: usize,
+    stacks: Vec<Stack>,
+    history: Vec<Vec<Lift>>,
+    warnings: Vec<Violation>,
+    halted: bool,
+}
+
+impl<'a> Replay<'a> {
+    pub fn new(setup: &'a Setup, crane: &'a dyn Crane, mode: Mode) -> Self {
+        Replay {
+            crane,
+            mode,
+            procedures: &setup.procedures,
+            first_line: setup.first_line,
+            stacks: setup.stacks.clone(),
+            history: vec![],
+            warnings: vec![],
+            halted: false,
+        }
+    }
+
+    /// How many procedures have been carried out or skipped.
+    pub fn step(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn stacks(&self) -> &[Stack] {
+        &self.stacks
+    }
+
+    /// The procedures skipped so far in lenient mode.
+    pub fn warnings(&self) -> &[Violation] {
+        &self.warnings
+    }
+
+    /// Carries out the next procedure. An impossible one is an error in
+    /// strict mode, and is skipped with a warning in lenient mode. Returns
+    /// `false` once every procedure is done.
+    pub fn forward(&mut self) -> Result<bool, Violation> {
+        let step = self.step();
+        let Some(procedure) = self.procedures.get(step) else {
+            return Ok(false);
+        };
+        let lifts = match procedure.lifts(&self.stacks, self.crane) {
+            Ok(lifts) => lifts,
+            Err(problem) => {
+                let violation = Violation {
+                    line: self.first_line + step,
+                    procedure: procedure.clone(),
+                    problem,
+                };
+                if self.mode == Mode::Strict {
+                    return Err(violation);
                 }
+                self.warnings.push(violation);
+                vec![]
             }
+        };
+        for lift in lifts.iter() {
+            lift.apply(&mut self.stacks);
         }
+        self.history.push(lifts);
+        Ok(true)
     }
 
-    fn get_top_crates(&self) -> String {
-        match self {
-            CrateMover::M9000(setup) => setup.get_top_crates(),
-            CrateMover::M9001(setup) => setup.get_top_crates(),
+    /// Undoes the last procedure by making its lifts in reverse. Returns
+    /// `false` when back at the start.
+    pub fn back(&mut self) -> bool {
+        let Some(lifts) = self.history.pop() else {
+            return false;
+        };
+        for lift in lifts.into_iter().rev() {
+            lift.inverse().apply(&mut self.stacks);
         }
+        let step = self.step();
+        self.warnings.retain(|w| w.line < self.first_line + step);
+        self.halted = false;
+        true
     }
 }
 
-#[derive(Debug, Clone)]
+impl Iterator for Replay<'_> {
+    type Item = Result<Vec<Stack>, Violation>;
+
+    /// The stacks after each procedure in turn, ending after the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.halted {
+            return None;
+        }
+        match self.forward() {
+            Ok(true) => Some(Ok(self.stacks.clone())),
+            Ok(false) => None,
+            Err(violation) => {
+                self.halted = true;
+                Some(Err(violation))
+            }
+        }
+    }
+}
+
+/// The crate on top of each stack, or `None` for an empty stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TopCrates(Vec<Option<char>>);
+
+impl fmt::Display for TopCrates {
+    /// Writes the crate letters, with `_` standing in for an empty stack.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0
+            .iter()
+            .try_for_each(|c| write!(f, "{}", c.unwrap_or('_')))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Setup {
     stacks: Vec<Stack>,
     procedures: Vec<Procedure>,
+    /// The input line number of the first procedure.
+    first_line: usize,
 }
 
 impl FromStr for Setup {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (stacks, procedures) = s.split_once(MULTILINE).unwrap();
-        let stacks: Vec<Vec<char>> = stacks.lines().map(|l| l.chars().collect()).collect();
+        let (stacks, procedures) = s.split_once(MULTILINE).ok_or(())?;
+        let first_line = stacks.lines().count() + 2;
+        let lines: Vec<Vec<char>> = stacks.lines().map(|l| l.chars().collect()).collect();
+        let (labels, rows) = lines.split_last().ok_or(())?;
+
+        // lines may have lost their trailing spaces, so go by the stack
+        // numbers and read anything missing above them as empty
+        let stacks: Vec<Stack> = labels
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_ascii_digit())
+            .map(|(i, _)| {
+                rows.iter()
+                    .rev()
+                    .filter_map(|row| row.get(i).copied().filter(char::is_ascii_uppercase))
+                    .collect()
+            })
+            .collect();
+        if stacks.is_empty() {
+            return Err(());
+        }
 
         Ok(Setup {
-            stacks: (0..stacks[0].len())
-                .filter_map(|i| {
-                    stacks[stacks.len() - 1][i].is_ascii_digit().then_some(
-                        (0..stacks.len())
-                            .rev()
-                            .filter_map(|j| {
-                                stacks[j][i].is_ascii_uppercase().then_some(stacks[j][i])
-                            })
-                            .collect(),
-                    )
-                })
-                .collect(),
-            procedures: procedures.lines().map(|l| l.parse().unwrap()).collect(),
+            stacks,
+            procedures: procedures
+                .lines()
+                .map(|l| l.parse())
+                .collect::<Result<_, _>>()?,
+            first_line,
         })
     }
 }
 
+impl fmt::Display for Setup {
+    /// Writes the drawing and the procedures back in the input format, so the
+    /// result parses to the same setup.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{MULTILINE}", render(&self.stacks))?;
+        for (i, procedure) in self.procedures.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{SINGLELINE}")?;
+            }
+            write!(f, "{procedure}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Setup {
-    fn get_top_crates(&self) -> String {
-        self.stacks
-            .iter()
-            .map(|s| s.crates[s.crates.len() - 1])
-            .collect()
+    /// Carries out every procedure with `crane`, returning the warnings
+    /// recorded in lenient mode or the first impossible procedure in strict
+    /// mode.
+    fn commit_procedures(
+        &mut self,
+        crane: &dyn Crane,
+        mode: Mode,
+    ) -> Result<Vec<Violation>, Violation> {
+        let mut replay = Replay::new(self, crane, mode);
+        while replay.forward()? {}
+        let (stacks, warnings) = (replay.stacks, replay.warnings);
+        self.stacks = stacks;
+        Ok(warnings)
+    }
+
+    /// Checks every procedure against the stacks as they would be at that
+    /// point, reporting each impossible one.
+    fn validate(&self, crane: &dyn Crane) -> Vec<Violation> {
+        let mut replay = Replay::new(self, crane, Mode::Lenient);
+        while let Ok(true) = replay.forward() {}
+        replay.warnings
+    }
+
+    fn top_crates(&self) -> TopCrates {
+        TopCrates(
+            self.stacks
+                .iter()
+                .map(|s| s.crates.last().copied())
+                .collect(),
+        )
     }
 }
 
 fn main() {
-    let mut crate_mover_9000 = CrateMover::M9000(read_input::<Setup>("d05/input.txt"));
-    let mut crate_mover_9001 = CrateMover::M9001(crate_mover_9000.setup());
+    let opt = Opt::parse();
+    let setup = read_input::<Setup>("d05/input.txt");
+    let mode = if opt.strict {
+        Mode::Strict
+    } else {
+        Mode::Lenient
+    };
+    let fail = |violation: Violation| -> ! {
+        eprintln!("error: {violation}");
+        std::process::exit(1)
+    };
+    let top_crates = |crane: &dyn Crane| {
+        let mut setup = setup.clone();
+        let warnings = setup
+            .commit_procedures(crane, mode)
+            .unwrap_or_else(|v| fail(v));
+        for warning in warnings {
+            println!("warning: {}: {warning}", crane.name());
+        }
+        setup.top_crates()
+    };
 
-    crate_mover_9000.commit_procedures();
-    crate_mover_9001.commit_procedures();
+    output!(top_crates(&CrateMover9000), top_crates(&CrateMover9001));
+    for name in opt.cranes {
+        let crane = crane(&name).expect("expect a known crane model");
+        println!("{}: {}", crane.name(), top_crates(&*crane));
+    }
 
-    output!(
-        crate_mover_9000.get_top_crates(),
-        crate_mover_9001.get_top_crates()
-    );
+    if let Some(name) = opt.validate {
+        let crane = crane(&name).expect("expect a known crane model");
+        let violations = setup.validate(&*crane);
+        println!(
+            "{}: {} impossible procedures",
+            crane.name(),
+            violations.len()
+        );
+        for violation in violations {
+            println!("{violation}");
+        }
+    }
+
+    if let Some(name) = opt.show {
+        let crane = crane(&name).expect("expect a known crane model");
+        let mut setup = setup.clone();
+        setup
+            .commit_procedures(&*crane, mode)
+            .unwrap_or_else(|v| fail(v));
+        println!("{setup}");
+    }
+
+    if let Some(name) = opt.replay {
+        let crane = crane(&name).expect("expect a known crane model");
+        let mut replay = Replay::new(&setup, &*crane, mode);
+        println!("{}\n", render(replay.stacks()));
+        for (step, stacks) in replay.by_ref().enumerate() {
+            let stacks = stacks.unwrap_or_else(|v| fail(v));
+            println!(
+                "After {} ({}):\n{}\n",
+                setup.procedures[step],
+                step + 1,
+                render(&stacks)
+            );
+        }
+        for warning in replay.warnings() {
+            println!("warning: {warning}");
+        }
+        for _ in 0..opt.rewind {
+            replay.back();
+        }
+        if opt.rewind > 0 {
+            println!(
+                "Rewound to step {}:\n{}",
+                replay.step(),
+                render(replay.stacks())
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+        move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2";
+
+    fn setup(stacks: &[&str], procedures: &[(usize, usize, usize)]) -> Setup {
+        Setup {
+            stacks: stacks.iter().map(|s| s.chars().collect()).collect(),
+            procedures: procedures
+                .iter()
+                .map(|&(n, from, to)| Procedure { n, from, to })
+                .collect(),
+            first_line: 0,
+        }
+    }
+
+    /// Checks that writing and re-reading gives back the same stacks and
+    /// procedures; line numbers depend on how tall the drawing is.
+    fn assert_round_trip(setup: &Setup) {
+        let parsed: Setup = setup.to_string().parse().unwrap();
+        assert_eq!(parsed.stacks, setup.stacks, "{setup}");
+        assert_eq!(parsed.procedures, setup.procedures, "{setup}");
+    }
+
+    #[test]
+    fn trimmed_drawing() {
+        let mut setup: Setup = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1"
+            .parse()
+            .unwrap();
+        assert_round_trip(&setup);
+        setup
+            .commit_procedures(&CrateMover9000, Mode::Strict)
+            .unwrap();
+        assert_eq!(setup.top_crates().to_string(), "DCP");
+    }
+
+    #[test]
+    fn no_stacks() {
+        assert_eq!("\n\nmove 1 from 1 to 2".parse::<Setup>(), Err(()));
+        assert_eq!("[A]\n\nmove 1 from 1 to 2".parse::<Setup>(), Err(()));
+        assert_eq!(" 1 ".parse::<Setup>(), Err(()));
+    }
+
+    #[test]
+    fn example_round_trip() {
+        let setup: Setup = EXAMPLE.parse().unwrap();
+        assert_eq!(setup.to_string(), EXAMPLE);
+        assert_eq!(setup.to_string().parse(), Ok(setup));
+    }
+
+    #[test]
+    fn built_round_trip() {
+        for setup in [
+            setup(&["A"], &[]),
+            setup(&["", "", ""], &[(0, 1, 3)]),
+            setup(&["ABC", "", "D"], &[(2, 1, 3), (10, 2, 2)]),
+            setup(&["", "XYZXYZXYZ"], &[(9, 2, 1)]),
+            setup(
+                &["A", "BB", "CCC", "", "E", "FFFFFF", "", "HH", "I"],
+                &[(1, 9, 1), (3, 3, 4)],
+            ),
+        ] {
+            assert_round_trip(&setup);
+        }
+    }
+
+    #[test]
+    fn final_state_round_trip() {
+        let mut setup: Setup = EXAMPLE.parse().unwrap();
+        setup
+            .commit_procedures(&CrateMover9000, Mode::Strict)
+            .unwrap();
+        assert_eq!(setup.top_crates().to_string(), "CMZ");
+        assert_round_trip(&setup);
+    }
+
+    #[test]
+    fn undo_restores_every_step() {
+        let mut setup: Setup = EXAMPLE.parse().unwrap();
+        // moving onto the same stack, and a move lenient mode has to skip
+        setup.procedures.push(Procedure {
+            n: 2,
+            from: 3,
+            to: 3,
+        });
+        setup.procedures.push(Procedure {
+            n: 9,
+            from: 1,
+            to: 2,
+        });
+
+        for name in ["9000", "9001", "limited:2", "fixed:2"] {
+            let crane = crane(name).unwrap();
+            let mut replay = Replay::new(&setup, &*crane, Mode::Lenient);
+            let snapshots: Vec<Vec<Stack>> = replay.by_ref().map(Result::unwrap).collect();
+            assert_eq!(snapshots.len(), setup.procedures.len(), "{name}");
+            assert!(!replay.warnings().is_empty(), "{name}");
+
+            for step in (0..snapshots.len()).rev() {
+                assert_eq!(replay.stacks(), snapshots[step], "{name} at {step}");
+                assert!(replay.back());
+            }
+            assert_eq!(replay.stacks(), setup.stacks, "{name}");
+            assert!(replay.warnings().is_empty(), "{name}");
+            assert!(!replay.back());
+        }
+    }
+
+    #[test]
+    fn impossible_procedures() {
+        let input = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+            move 1 from 2 to 4\nmove 3 from 3 to 1\nmove 2 from 1 to 3\n\
+            move 1 from 1 to 2\nmove 1 from 0 to 2";
+        let setup: Setup = input.parse().unwrap();
+        let violation = |line, problem| Violation {
+            line,
+            procedure: setup.procedures[line - 6].clone(),
+            problem,
+        };
+        let expected = vec![
+            violation(6, Problem::NoSuchStack(4)),
+            violation(
+                7,
+                Problem::TooFewCrates {
+                    stack: 3,
+                    available: 1,
+                },
+            ),
+            violation(
+                9,
+                Problem::TooFewCrates {
+                    stack: 1,
+                    available: 0,
+                },
+            ),
+            violation(10, Problem::NoSuchStack(0)),
+        ];
+        assert_eq!(setup.validate(&CrateMover9000), expected);
+
+        let mut lenient = setup.clone();
+        assert_eq!(
+            lenient.commit_procedures(&CrateMover9000, Mode::Lenient),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            lenient.top_crates(),
+            TopCrates(vec![None, Some('D'), Some('Z')])
+        );
+        assert_eq!(lenient.top_crates().to_string(), "_DZ");
+
+        let mut strict = setup.clone();
+        assert_eq!(
+            strict.commit_procedures(&CrateMover9000, Mode::Strict),
+            Err(expected[0].clone())
+        );
+    }
+
+    #[test]
+    fn unliftable_procedures() {
+        let setup: Setup = EXAMPLE.parse().unwrap();
+        let violations = setup.validate(&Fixed { size: 2 });
+        assert_eq!(
+            violations
+                .iter()
+                .map(|v| (v.line, v.problem.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (6, Problem::Unliftable),
+                (
+                    7,
+                    Problem::TooFewCrates {
+                        stack: 1,
+                        available: 2,
+                    },
+                ),
+                (9, Problem::Unliftable),
+            ]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "line 6: move 1 from 2 to 1: the crane cannot move that many crates"
+        );
+    }
 }
@@ -1,16 +1,195 @@
 use aoc::*;
-use itertools::Itertools;
+use clap::Parser;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufReader, Bytes, Read};
+
+#[derive(Parser, Debug)]
+#[clap(name = "d06", about = "Tuning Trouble")]
+struct Opt {
+    /// further marker sizes to look for besides 4 and 14
+    #[clap(short, long, value_delimiter = ',')]
+    sizes: Vec<usize>,
+
+    /// list every marker position, not just the first
+    #[clap(short, long)]
+    all: bool,
+}
+
+/// Spots markers, runs of `size` distinct symbols, one symbol at a time.
+///
+/// Remembers where each symbol was last seen, so the start of the longest run
+/// of distinct symbols ending at the current one only ever moves forward and
+/// each symbol costs O(1) whatever the marker size.
+#[derive(Debug, Clone)]
+struct Detector<T> {
+    size: usize,
+    last_seen: HashMap<T, usize>,
+    start: usize,
+    position: usize,
+}
+
+impl<T: Hash + Eq> Detector<T> {
+    pub fn new(size: usize) -> Self {
+        Detector {
+            size,
+            last_seen: HashMap::new(),
+            start: 0,
+            position: 0,
+        }
+    }
+
+    /// Takes the next symbol and returns how many symbols have been read so
+    /// far if they end with a marker.
+    pub fn push(&mut self, symbol: T) -> Option<usize> {
+        if let Some(seen) = self.last_seen.insert(symbol, self.position) {
+            self.start = self.start.max(seen + 1);
+        }
+        self.position += 1;
+        (self.position - self.start >= self.size).then_some(self.position)
+    }
+}
+
+/// Decodes UTF-8 characters from a byte stream one at a time.
+struct Chars<R> {
+    bytes: Bytes<BufReader<R>>,
+}
+
+impl<R: Read> Chars<R> {
+    pub fn new(reader: R) -> Self {
+        Chars {
+            bytes: BufReader::new(reader).bytes(),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Chars<R> {
+    type Item = io::Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = match self.bytes.next()? {
+            Ok(b) => b,
+            Err(e) => return Some(Err(e)),
+        };
+        let width = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0,
+        };
+        let mut buf = [first, 0, 0, 0];
+        for b in buf.iter_mut().take(width).skip(1) {
+            match self.bytes.next() {
+                Some(Ok(next)) => *b = next,
+                Some(Err(e)) => return Some(Err(e)),
+                None => break,
+            }
+        }
+        Some(
+            std::str::from_utf8(&buf[..width])
+                .ok()
+                .and_then(|s| s.chars().next())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8")),
+        )
+    }
+}
+
+/// Every marker position, counted in characters, for each of `sizes`, all
+/// found in a single pass over `reader`. The datastream ends at the first line
+/// terminator, if there is one.
+fn markers<R: Read>(reader: R, sizes: &[usize]) -> io::Result<Vec<Vec<usize>>> {
+    let mut detectors: Vec<Detector<char>> = sizes.iter().map(|&s| Detector::new(s)).collect();
+    let mut markers: Vec<Vec<usize>> = vec![vec![]; sizes.len()];
+    for c in Chars::new(reader) {
+        let c = c?;
+        if c == '\n' || c == '\r' {
+            break;
+        }
+        for (detector, found) in detectors.iter_mut().zip(markers.iter_mut()) {
+            found.extend(detector.push(c));
+        }
+    }
+    Ok(markers)
+}
 
 fn main() {
-    let input = read_input::<String>("d06/input.txt");
-    let message = |size| {
-        input
-            .as_bytes()
-            .windows(size)
-            .position(|marker| marker.iter().all_unique())
-            .unwrap()
-            + size
+    let opt = Opt::parse();
+    let sizes: Vec<usize> = [4, 14].into_iter().chain(opt.sizes).collect();
+    let markers = markers(File::open("d06/input.txt").expect("no such file"), &sizes)
+        .expect("expect able to read input file");
+    let first = |i: usize| {
+        markers[i]
+            .first()
+            .map_or("none".to_string(), |p| p.to_string())
     };
+    output!(first(0), first(1));
+    for (i, size) in sizes.iter().enumerate().skip(2) {
+        println!("Size {size}: {}", first(i));
+    }
+    if opt.all {
+        for (size, found) in sizes.iter().zip(markers.iter()) {
+            println!(
+                "Size {size} markers: {}",
+                found
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first(stream: &str, size: usize) -> Option<usize> {
+        markers(stream.as_bytes(), &[size]).unwrap()[0]
+            .first()
+            .copied()
+    }
+
+    #[test]
+    fn examples() {
+        for (stream, packet, message) in [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ] {
+            assert_eq!(first(stream, 4), Some(packet), "{stream}");
+            assert_eq!(first(stream, 14), Some(message), "{stream}");
+        }
+    }
+
+    #[test]
+    fn all_markers() {
+        assert_eq!(
+            markers("abcabd".as_bytes(), &[3, 4]).unwrap(),
+            vec![vec![3, 4, 5, 6], vec![6]]
+        );
+    }
+
+    #[test]
+    fn line_terminator() {
+        let expected = vec![vec![3, 4, 5, 6], vec![6]];
+        for stream in ["abcabd\n", "abcabd\r\n", "abcabd\nxyz\n"] {
+            assert_eq!(markers(stream.as_bytes(), &[3, 4]).unwrap(), expected);
+        }
+    }
 
-    output!(message(4), message(14));
+    #[test]
+    fn non_ascii() {
+        assert_eq!(
+            markers("ééàà".as_bytes(), &[2, 3]).unwrap(),
+            vec![vec![3], vec![]]
+        );
+        assert_eq!(first("ab€😀ab", 4), Some(4));
+        assert!(markers(&[b'a', 0xc3][..], &[2]).is_err());
+        assert!(markers(&[0xff, b'a'][..], &[2]).is_err());
+    }
 }
@@ -1,201 +1,666 @@
 use aoc::*;
-use std::any::Any;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write;
 use std::str::FromStr;
 
-const DISK_SIZE: usize = 70_000_000;
-const REQUIRED_DISK_SPACE: usize = 30_000_000;
+#[derive(Parser, Debug)]
+#[clap(name = "d07", about = "No Space Left On Device")]
+struct Opt {
+    /// the total size of the disk
+    #[clap(long, default_value = "70000000", global = true)]
+    disk_size: usize,
 
-type List = Vec<Box<dyn FileType>>;
+    /// the free space the update needs
+    #[clap(long, default_value = "30000000", global = true)]
+    required: usize,
+
+    #[clap(subcommand)]
+    query: Option<Query>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Query {
+    /// Draw the tree below a directory with the size of every entry
+    Tree {
+        #[clap(default_value = "/")]
+        path: String,
+    },
+    /// List the directories below a directory, largest first
+    Du {
+        #[clap(default_value = "/")]
+        path: String,
+
+        /// how many levels below `path` to include
+        #[clap(short, long)]
+        depth: Option<usize>,
+    },
+    /// List the entries below a directory that match every given test
+    Find {
+        #[clap(default_value = "/")]
+        path: String,
+
+        /// a name pattern, where `*` matches any run of characters and `?`
+        /// any single one
+        #[clap(long)]
+        name: Option<String>,
+
+        #[clap(long, value_enum)]
+        kind: Option<Kind>,
+
+        #[clap(long)]
+        min_size: Option<usize>,
+
+        #[clap(long)]
+        max_size: Option<usize>,
+    },
+    /// Report the directories that would free enough space for the update
+    Clean,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    File,
+    Directory,
+}
+
+/// An index into the file system's arena of entries.
+type NodeId = usize;
 
 #[derive(Debug)]
 enum Command {
     ChangeDirectory(String),
-    List(List),
+    List,
 }
 
-trait FileType: std::fmt::Debug + FileTypeToAny {
-    fn name(&self) -> String;
-    fn size(&self) -> usize;
+impl FromStr for Command {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_whitespace().collect::<Vec<_>>()[..] {
+            ["cd", target] => Ok(Command::ChangeDirectory(target.to_string())),
+            ["ls"] => Ok(Command::List),
+            _ => Err(()),
+        }
+    }
 }
 
-trait FileTypeToAny: 'static {
-    fn as_any_mut(&mut self) -> &mut dyn Any;
-    fn as_any_ref(&self) -> &dyn Any;
+/// What `ls` says about an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Listing {
+    Directory,
+    File(usize),
 }
 
-impl<T: 'static> FileTypeToAny for T {
-    fn as_any_mut(&mut self) -> &mut dyn Any {
-        self
+/// Reads a line of `ls` output into the entry's name and what it is.
+fn listing(s: &str) -> Result<(&str, Listing), ()> {
+    let (listing, name) = s.split_once(' ').ok_or(())?;
+    if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+        return Err(());
     }
-    fn as_any_ref(&self) -> &dyn Any {
-        self
+    match listing {
+        "dir" => Ok((name, Listing::Directory)),
+        size => Ok((name, Listing::File(size.parse().map_err(|_| ())?))),
     }
 }
 
-#[derive(Debug, Clone)]
-struct File {
-    name: String,
-    size: usize,
+/// Why a transcript line does not make sense.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Problem {
+    UnknownCommand(String),
+    BadListing(String),
+    OutputWithoutCommand,
+    NotADirectory(String),
+    Conflict(String),
 }
 
-impl File {
-    pub fn new(name: String, size: usize) -> Self {
-        File { name, size }
-    }
+/// A problem with the transcript and the line it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TranscriptError {
+    line: usize,
+    problem: Problem,
 }
 
-impl FileType for File {
-    fn name(&self) -> String {
-        self.name.clone()
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.problem {
+            Problem::UnknownCommand(command) => write!(f, "unknown command `{command}`"),
+            Problem::BadListing(listing) => write!(f, "cannot read `{listing}`"),
+            Problem::OutputWithoutCommand => write!(f, "output without an `ls` before it"),
+            Problem::NotADirectory(path) => write!(f, "{path} is not a directory"),
+            Problem::Conflict(path) => write!(f, "{path} was listed differently before"),
+        }
     }
+}
 
-    fn size(&self) -> usize {
-        self.size
-    }
+#[derive(Debug, Clone)]
+enum Node {
+    File {
+        size: usize,
+    },
+    Directory {
+        children: HashMap<String, NodeId>,
+        /// The total size of everything below, filled in once the tree is
+        /// complete.
+        size: usize,
+    },
 }
 
-#[derive(Debug)]
-struct Directory {
+#[derive(Debug, Clone)]
+struct Entry {
     name: String,
-    children: List,
+    parent: Option<NodeId>,
+    node: Node,
 }
 
-impl Directory {
-    pub fn new(name: String) -> Self {
-        Directory {
-            name,
-            children: vec![],
-        }
-    }
-
-    pub fn ingest<I: IntoIterator<Item = Command>>(
-        &mut self,
-        iter: I,
-    ) -> <I as IntoIterator>::IntoIter {
-        let mut input = iter.into_iter();
-        while let Some(command) = input.next() {
-            match command {
-                Command::ChangeDirectory(name) => {
-                    if name != *".." {
-                        input = if let Some(dir) =
-                            (**self.children.iter_mut().find(|c| c.name() == name).unwrap())
-                                .as_any_mut()
-                                .downcast_mut::<Directory>()
-                        {
-                            dir.ingest(input)
-                        } else {
-                            input
-                        };
-                    } else {
-                        break;
+/// The file system as an arena of entries, the root first and every entry
+/// after its parent.
+#[derive(Debug, Clone)]
+struct FileSystem {
+    entries: Vec<Entry>,
+    paths: HashMap<String, NodeId>,
+}
+
+impl FileSystem {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        FileSystem {
+            entries: vec![Entry {
+                name: "/".to_string(),
+                parent: None,
+                node: Node::Directory {
+                    children: HashMap::new(),
+                    size: 0,
+                },
+            }],
+            paths: HashMap::from([("/".to_string(), Self::ROOT)]),
+        }
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.entries[id].name
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.entries[id].parent
+    }
+
+    pub fn is_directory(&self, id: NodeId) -> bool {
+        matches!(self.entries[id].node, Node::Directory { .. })
+    }
+
+    pub fn size(&self, id: NodeId) -> usize {
+        match self.entries[id].node {
+            Node::File { size } | Node::Directory { size, .. } => size,
+        }
+    }
+
+    pub fn child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+        match &self.entries[id].node {
+            Node::Directory { children, .. } => children.get(name).copied(),
+            Node::File { .. } => None,
+        }
+    }
+
+    /// The absolute path of an entry, such as `/a/e/i`.
+    pub fn path(&self, id: NodeId) -> String {
+        match self.parent(id) {
+            None => "/".to_string(),
+            Some(parent) => join(&self.path(parent), self.name(id)),
+        }
+    }
+
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        match path.trim_end_matches('/') {
+            "" => self.paths.get("/").copied(),
+            path => self.paths.get(path).copied(),
+        }
+    }
+
+    /// The entries of a directory, sorted by name.
+    pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+        let Node::Directory { children, .. } = &self.entries[id].node else {
+            return vec![];
+        };
+        let mut children: Vec<NodeId> = children.values().copied().collect();
+        children.sort_unstable_by_key(|&c| self.name(c));
+        children
+    }
+
+    /// Every entry below `id`, parents before children, each with how many
+    /// levels below `id` it is.
+    pub fn descendants(&self, id: NodeId) -> Vec<(NodeId, usize)> {
+        let mut descendants = vec![];
+        let mut stack = vec![(id, 0)];
+        while let Some((id, depth)) = stack.pop() {
+            descendants.push((id, depth));
+            stack.extend(self.children(id).into_iter().rev().map(|c| (c, depth + 1)));
+        }
+        descendants
+    }
+
+    /// Draws the tree below `id` the way the puzzle does.
+    pub fn tree(&self, id: NodeId) -> String {
+        let mut out = String::new();
+        for (id, depth) in self.descendants(id) {
+            let kind = if self.is_directory(id) { "dir" } else { "file" };
+            writeln!(
+                out,
+                "{:indent$}- {} ({kind}, size={})",
+                "",
+                self.name(id),
+                self.size(id),
+                indent = depth * 2
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// The directories below `id` down to `depth` levels, largest first.
+    pub fn du(&self, id: NodeId, depth: Option<usize>) -> Vec<NodeId> {
+        let mut directories: Vec<NodeId> = self
+            .descendants(id)
+            .into_iter()
+            .filter(|&(d, level)| self.is_directory(d) && depth.is_none_or(|max| level <= max))
+            .map(|(d, _)| d)
+            .collect();
+        directories.sort_by_key(|&d| std::cmp::Reverse(self.size(d)));
+        directories
+    }
+
+    pub fn find(&self, id: NodeId, filter: &Filter) -> Vec<NodeId> {
+        self.descendants(id)
+            .into_iter()
+            .map(|(id, _)| id)
+            .filter(|&id| filter.matches(self, id))
+            .collect()
+    }
+
+    /// Every directory whose deletion would leave `required` free on a disk
+    /// of `disk_size`, smallest first, or none if there is enough already.
+    pub fn cleanup(&self, disk_size: usize, required: usize) -> Cleanup {
+        let used = self.size(Self::ROOT);
+        let free = disk_size.saturating_sub(used);
+        let needed = required.saturating_sub(free);
+        let mut candidates: Vec<(String, usize)> = self
+            .directories()
+            .filter(|&d| needed > 0 && self.size(d) >= needed)
+            .map(|d| (self.path(d), self.size(d)))
+            .collect();
+        candidates.sort_unstable_by_key(|(_, size)| *size);
+        Cleanup {
+            used,
+            free,
+            needed,
+            candidates,
+        }
+    }
+
+    fn listing(&self, id: NodeId) -> Listing {
+        match self.entries[id].node {
+            Node::File { size } => Listing::File(size),
+            Node::Directory { .. } => Listing::Directory,
+        }
+    }
+
+    /// Adds an entry to directory `parent`, returning the existing one if the
+    /// same entry was listed before.
+    pub fn add(&mut self, parent: NodeId, name: &str, listing: Listing) -> Result<NodeId, Problem> {
+        if let Some(id) = self.child(parent, name) {
+            if self.listing(id) != listing {
+                return Err(Problem::Conflict(self.path(id)));
+            }
+            return Ok(id);
+        }
+        let id = self.entries.len();
+        let Node::Directory { children, .. } = &mut self.entries[parent].node else {
+            return Err(Problem::NotADirectory(self.path(parent)));
+        };
+        children.insert(name.to_string(), id);
+        self.entries.push(Entry {
+            name: name.to_string(),
+            parent: Some(parent),
+            node: match listing {
+                Listing::Directory => Node::Directory {
+                    children: HashMap::new(),
+                    size: 0,
+                },
+                Listing::File(size) => Node::File { size },
+            },
+        });
+        self.paths.insert(self.path(id), id);
+        Ok(id)
+    }
+
+    /// Follows `path` from directory `cwd` the way `cd` would. Directories
+    /// along the way that were never listed are added, since the transcript
+    /// shows that they exist.
+    pub fn resolve(&mut self, cwd: NodeId, path: &str) -> Result<NodeId, Problem> {
+        if let Some(id) = self.lookup(path).filter(|&id| self.is_directory(id)) {
+            return Ok(id);
+        }
+        let start = if path.starts_with('/') {
+            Self::ROOT
+        } else {
+            cwd
+        };
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .try_fold(start, |dir, segment| match segment {
+                "." => Ok(dir),
+                ".." => Ok(self.parent(dir).unwrap_or(Self::ROOT)),
+                name => match self.child(dir, name) {
+                    Some(id) if !self.is_directory(id) => {
+                        Err(Problem::NotADirectory(self.path(id)))
                     }
+                    _ => self.add(dir, name, Listing::Directory),
+                },
+            })
+    }
+
+    /// Works out every directory size in one pass from the last entry to the
+    /// first, since children always come after their parents.
+    fn compute_sizes(&mut self) {
+        for entry in self.entries.iter_mut() {
+            if let Node::Directory { size, .. } = &mut entry.node {
+                *size = 0;
+            }
+        }
+        for id in (1..self.entries.len()).rev() {
+            let size = self.size(id);
+            if let Some(parent) = self.parent(id) {
+                if let Node::Directory { size: total, .. } = &mut self.entries[parent].node {
+                    *total += size;
                 }
-                Command::List(ls) => self.children.extend(ls.into_iter()),
             }
         }
-        input
     }
 
-    fn flatten(&self) -> Vec<&Directory> {
-        [
-            vec![self],
-            self.children
-                .iter()
-                .filter_map(|c| (**c).as_any_ref().downcast_ref::<Directory>())
-                .flat_map(|dir| dir.flatten())
-                .collect(),
-        ]
-        .concat()
+    pub fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.entries.len()).filter(|&id| self.is_directory(id))
     }
+}
 
-    pub fn iter(&self) -> DirectoryIter {
-        DirectoryIter {
-            files: self.flatten(),
-            count: 0,
-        }
+/// The tests an entry has to pass to be found; a missing test always passes.
+#[derive(Debug, Clone, Default)]
+struct Filter {
+    name: Option<String>,
+    kind: Option<Kind>,
+    min_size: Option<usize>,
+    max_size: Option<usize>,
+}
+
+impl Filter {
+    fn matches(&self, fs: &FileSystem, id: NodeId) -> bool {
+        let kind = if fs.is_directory(id) {
+            Kind::Directory
+        } else {
+            Kind::File
+        };
+        self.name.as_ref().is_none_or(|n| glob(n, fs.name(id)))
+            && self.kind.is_none_or(|k| k == kind)
+            && self.min_size.is_none_or(|min| fs.size(id) >= min)
+            && self.max_size.is_none_or(|max| fs.size(id) <= max)
     }
+}
 
-    pub fn size(&self) -> usize {
-        self.children.iter().map(|f| f.size()).sum()
+/// Matches `name` against a shell-style pattern, backtracking to the last
+/// `*` whenever the rest of the pattern fails.
+fn glob(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    star = Some((sp, sn + 1));
+                    p = sp + 1;
+                    n = sn + 1;
+                }
+                None => return false,
+            },
+        }
     }
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
-impl FileType for Directory {
-    fn name(&self) -> String {
-        self.name.clone()
+/// How much space the update is short of and which directories would free
+/// enough of it.
+#[derive(Debug, Clone)]
+struct Cleanup {
+    used: usize,
+    free: usize,
+    needed: usize,
+    candidates: Vec<(String, usize)>,
+}
+
+impl fmt::Display for Cleanup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Used {}, free {}, still needed {}",
+            self.used, self.free, self.needed
+        )?;
+        if self.needed == 0 {
+            return writeln!(f, "Nothing needs deleting");
+        }
+        for (i, (path, size)) in self.candidates.iter().enumerate() {
+            let best = if i == 0 { "  <- smallest" } else { "" };
+            writeln!(f, "{size:>10}  {path}{best}")?;
+        }
+        Ok(())
     }
+}
 
-    fn size(&self) -> usize {
-        self.children.iter().map(|f| f.size()).sum()
+fn join(directory: &str, name: &str) -> String {
+    match directory {
+        "/" => format!("/{name}"),
+        _ => format!("{directory}/{name}"),
     }
 }
 
-impl FromStr for Directory {
-    type Err = ();
+impl FromStr for FileSystem {
+    type Err = TranscriptError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut iter = s.split("$ ").skip(1).map(|s| {
-            if let Some(("cd", name)) = s.split_once(' ') {
-                Command::ChangeDirectory(name.trim_end().to_string())
-            } else {
-                Command::List(
-                    s.lines()
-                        .skip(1)
-                        .map(|l| {
-                            let (file_type, name) = l.split_once(' ').unwrap();
-                            let name = name.trim_end().to_string();
-                            match file_type {
-                                "dir" => Box::new(Directory::new(name)) as Box<dyn FileType>,
-                                size => Box::new(File::new(name, size.parse().unwrap())),
-                            }
-                        })
-                        .collect(),
-                )
+        let mut fs = FileSystem::new();
+        let mut cwd = Self::ROOT;
+        let mut listing_directory = false;
+
+        for (i, line) in s.lines().enumerate() {
+            let error = |problem| TranscriptError {
+                line: i + 1,
+                problem,
+            };
+            if let Some(command) = line.strip_prefix("$ ") {
+                listing_directory = false;
+                match command.parse() {
+                    Ok(Command::ChangeDirectory(path)) => {
+                        cwd = fs.resolve(cwd, &path).map_err(error)?;
+                    }
+                    Ok(Command::List) => listing_directory = true,
+                    Err(()) => return Err(error(Problem::UnknownCommand(command.to_string()))),
+                }
+            } else if !line.trim().is_empty() {
+                if !listing_directory {
+                    return Err(error(Problem::OutputWithoutCommand));
+                }
+                let (name, listing) =
+                    listing(line).map_err(|_| error(Problem::BadListing(line.to_string())))?;
+                fs.add(cwd, name, listing).map_err(error)?;
             }
+        }
+        fs.compute_sizes();
+        Ok(fs)
+    }
+}
+
+fn main() {
+    let opt = Opt::parse();
+    let fs = read_input::<String>("d07/input.txt")
+        .parse::<FileSystem>()
+        .unwrap_or_else(|e| {
+            eprintln!("error: {e}");
+            std::process::exit(1)
         });
+    let lookup = |path: &str| {
+        fs.lookup(path).unwrap_or_else(|| {
+            eprintln!("error: no such file or directory: {path}");
+            std::process::exit(1)
+        })
+    };
 
-        if let Some(Command::ChangeDirectory(name)) = iter.next() {
-            let mut root = Directory::new(name);
-            root.ingest(&mut iter);
-            Ok(root)
-        } else {
-            Err(())
+    match opt.query {
+        None => {
+            let small = Filter {
+                kind: Some(Kind::Directory),
+                max_size: Some(100000),
+                ..Filter::default()
+            };
+            let cleanup = fs.cleanup(opt.disk_size, opt.required);
+            output!(
+                fs.find(FileSystem::ROOT, &small)
+                    .into_iter()
+                    .map(|d| fs.size(d))
+                    .sum::<usize>(),
+                cleanup
+                    .candidates
+                    .first()
+                    .map_or("none".to_string(), |(_, size)| size.to_string())
+            );
+        }
+        Some(Query::Tree { path }) => print!("{}", fs.tree(lookup(&path))),
+        Some(Query::Du { path, depth }) => {
+            for d in fs.du(lookup(&path), depth) {
+                println!("{:>10}  {}", fs.size(d), fs.path(d));
+            }
         }
+        Some(Query::Find {
+            path,
+            name,
+            kind,
+            min_size,
+            max_size,
+        }) => {
+            let filter = Filter {
+                name,
+                kind,
+                min_size,
+                max_size,
+            };
+            for id in fs.find(lookup(&path), &filter) {
+                println!("{:>10}  {}", fs.size(id), fs.path(id));
+            }
+        }
+        Some(Query::Clean) => print!("{}", fs.cleanup(opt.disk_size, opt.required)),
     }
 }
 
-struct DirectoryIter<'a> {
-    files: Vec<&'a Directory>,
-    count: usize,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl<'a> Iterator for DirectoryIter<'a> {
-    type Item = &'a Directory;
+    const EXAMPLE: &str = "$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n\
+        $ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst\n$ cd e\n$ ls\n584 i\n\
+        $ cd ..\n$ cd ..\n$ cd d\n$ ls\n4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k";
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.count < self.files.len() {
-            self.count += 1;
-            Some(self.files[self.count - 1])
-        } else {
-            None
+    fn sizes(fs: &FileSystem) -> Vec<(String, usize)> {
+        let mut sizes: Vec<(String, usize)> = fs
+            .descendants(FileSystem::ROOT)
+            .into_iter()
+            .map(|(id, _)| (fs.path(id), fs.size(id)))
+            .collect();
+        sizes.sort();
+        sizes
+    }
+
+    fn error(transcript: &str) -> TranscriptError {
+        transcript.parse::<FileSystem>().unwrap_err()
+    }
+
+    #[test]
+    fn example() {
+        let fs: FileSystem = EXAMPLE.parse().unwrap();
+        for (path, size) in [
+            ("/", 48381165),
+            ("/a", 94853),
+            ("/a/e", 584),
+            ("/d", 24933642),
+        ] {
+            assert_eq!(fs.size(fs.lookup(path).unwrap()), size, "{path}");
         }
     }
-}
 
-fn main() {
-    let root = read_input::<Directory>("d07/input.txt");
-
-    output!(
-        root.iter()
-            .filter_map(|d| (d.size() <= 100000).then_some(d.size()))
-            .sum::<usize>(),
-        root.iter()
-            .filter_map(|d| {
-                (d.size() as isize - (REQUIRED_DISK_SPACE - (DISK_SIZE - root.size())) as isize
-                    >= 0)
-                    .then_some(d.size())
-            })
-            .min()
-            .unwrap()
-    );
+    #[test]
+    fn navigation() {
+        // starts without `cd /`, climbs above the root, jumps by absolute and
+        // multi-segment paths and lists directories more than once
+        let transcript = "$ cd ..\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n\
+            $ cd /a/e\n$ ls\n584 i\n$ cd ../..\n$ ls\ndir a\n14848514 b.txt\n\
+            $ cd a\n$ ls\ndir e\n29116 f\n2557 g\n62596 h.lst\n$ ls\n29116 f\n\
+            $ cd /\n$ cd ./d/\n$ ls\n4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k\n\
+            $ cd /\n$ ls\n8504156 c.dat";
+        let fs: FileSystem = transcript.parse().unwrap();
+        assert_eq!(sizes(&fs), sizes(&EXAMPLE.parse().unwrap()));
+    }
+
+    #[test]
+    fn inconsistent_transcripts() {
+        let cases = [
+            (
+                "$ cd /\n$ ls\n12 a\n$ ls\n13 a",
+                5,
+                Problem::Conflict("/a".to_string()),
+            ),
+            (
+                "$ cd /\n$ ls\ndir a\n$ ls\n13 a",
+                5,
+                Problem::Conflict("/a".to_string()),
+            ),
+            (
+                "$ cd /\n$ ls\n12 a\n$ cd a",
+                4,
+                Problem::NotADirectory("/a".to_string()),
+            ),
+            (
+                "$ cd /\n$ ls\n12 a\n$ cd /a/b",
+                4,
+                Problem::NotADirectory("/a".to_string()),
+            ),
+            ("$ cd /\n12 a", 2, Problem::OutputWithoutCommand),
+            ("$ ls\n12 a\n$ cd b\n1 c", 4, Problem::OutputWithoutCommand),
+            (
+                "$ cd /\n$ rm a",
+                2,
+                Problem::UnknownCommand("rm a".to_string()),
+            ),
+            ("$ ls\nfoo a", 2, Problem::BadListing("foo a".to_string())),
+            ("$ ls\n12 a/b", 2, Problem::BadListing("12 a/b".to_string())),
+        ];
+        for (transcript, line, problem) in cases {
+            assert_eq!(
+                error(transcript),
+                TranscriptError { line, problem },
+                "{transcript}"
+            );
+        }
+        assert_eq!(
+            error("$ cd /\n12 a").to_string(),
+            "line 2: output without an `ls` before it"
+        );
+    }
 }
@@ -0,0 +1,214 @@
+use aoc::*;
+use clap::Parser;
+use itertools::Itertools;
+use std::fmt;
+use std::str::FromStr;
+
+const SOURCE: Point = Point { x: 500, y: 0 };
+
+#[derive(Parser, Debug)]
+#[clap(name = "d14", about = "Regolith Reservoir")]
+struct Opt {
+    /// render the cave every time a unit of sand comes to rest
+    #[clap(short, long)]
+    render: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Point {
+    x: usize,
+    y: usize,
+}
+
+impl FromStr for Point {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s.trim().split_once(',').ok_or(())?;
+        Ok(Point {
+            x: x.parse().map_err(|_| ())?,
+            y: y.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Path(Vec<Point>);
+
+impl FromStr for Path {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Path(
+            s.split(" -> ")
+                .map(|p| p.parse())
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+impl Path {
+    fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.0.windows(2).flat_map(|w| {
+            let (a, b) = (w[0], w[1]);
+            (a.y.min(b.y)..=a.y.max(b.y))
+                .flat_map(move |y| (a.x.min(b.x)..=a.x.max(b.x)).map(move |x| Point { x, y }))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Air,
+    Rock,
+    Sand,
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Tile::Air => '.',
+                Tile::Rock => '#',
+                Tile::Sand => 'o',
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Cave {
+    tiles: Vec<Vec<Tile>>,
+    offset: usize,
+    depth: usize,
+    floor: Option<usize>,
+}
+
+impl FromStr for Cave {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let paths = s
+            .lines()
+            .map(|l| l.parse())
+            .collect::<Result<Vec<Path>, _>>()?;
+        let rocks = paths.iter().flat_map(|p| p.points()).collect::<Vec<_>>();
+
+        // Sand can drift at most one column per row it falls, so the floor
+        // of part 2 never needs to be wider than its depth on either side.
+        let depth = rocks.iter().map(|p| p.y).max().ok_or(())?;
+        let reach = depth + 3;
+        let offset = rocks
+            .iter()
+            .map(|p| p.x)
+            .chain([SOURCE.x.saturating_sub(reach)])
+            .min()
+            .unwrap();
+        let width = rocks
+            .iter()
+            .map(|p| p.x)
+            .chain([SOURCE.x + reach])
+            .max()
+            .unwrap()
+            - offset
+            + 1;
+
+        let mut tiles = vec![vec![Tile::Air; width]; reach];
+        for rock in rocks {
+            tiles[rock.y][rock.x - offset] = Tile::Rock;
+        }
+
+        Ok(Cave {
+            tiles,
+            offset,
+            depth,
+            floor: None,
+        })
+    }
+}
+
+impl Cave {
+    pub fn with_floor(mut self) -> Self {
+        self.floor = Some(self.depth + 2);
+        self
+    }
+
+    fn get(&self, p: Point) -> Tile {
+        if Some(p.y) == self.floor {
+            Tile::Rock
+        } else {
+            self.tiles[p.y][p.x - self.offset]
+        }
+    }
+
+    /// Lets a single unit of sand fall from the source and returns where it
+    /// came to rest, or `None` once sand spills into the abyss or the source
+    /// is blocked.
+    pub fn drop_sand(&mut self) -> Option<Point> {
+        if self.get(SOURCE) != Tile::Air {
+            return None;
+        }
+
+        let mut sand = SOURCE;
+        loop {
+            if self.floor.is_none() && sand.y > self.depth {
+                return None;
+            }
+            match [sand.x, sand.x - 1, sand.x + 1]
+                .into_iter()
+                .map(|x| Point { x, y: sand.y + 1 })
+                .find(|&p| self.get(p) == Tile::Air)
+            {
+                Some(next) => sand = next,
+                None => {
+                    self.tiles[sand.y][sand.x - self.offset] = Tile::Sand;
+                    return Some(sand);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Cave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (left, right) = self
+            .tiles
+            .iter()
+            .flat_map(|row| row.iter().positions(|t| *t != Tile::Air))
+            .chain([SOURCE.x - self.offset])
+            .minmax()
+            .into_option()
+            .unwrap();
+
+        for y in 0..=self.floor.unwrap_or(self.depth) {
+            for x in (left..=right).map(|x| x + self.offset) {
+                match self.get(Point { x, y }) {
+                    Tile::Air if (Point { x, y }) == SOURCE => write!(f, "+")?,
+                    tile => write!(f, "{tile}")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let opt = Opt::parse();
+    let cave = read_input::<Cave>("d14/input.txt");
+
+    let fill = |mut cave: Cave| {
+        let mut units = 0;
+        while cave.drop_sand().is_some() {
+            units += 1;
+            if opt.render {
+                println!("{cave}");
+            }
+        }
+        units
+    };
+
+    output!(fill(cave.clone()), fill(cave.with_floor()));
+}
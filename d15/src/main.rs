@@ -0,0 +1,167 @@
+use aoc::*;
+use clap::Parser;
+use std::str::FromStr;
+
+#[derive(Parser, Debug)]
+#[clap(name = "d15", about = "Beacon Exclusion Zone")]
+struct Opt {
+    /// the row on which to count covered positions
+    #[clap(long, default_value = "2000000")]
+    row: i64,
+
+    /// the upper bound of the square in which the distress beacon hides
+    #[clap(long, default_value = "4000000")]
+    limit: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl Point {
+    fn distance(&self, other: &Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Interval {
+    start: i64,
+    end: i64,
+}
+
+impl Interval {
+    fn len(&self) -> i64 {
+        self.end - self.start + 1
+    }
+
+    fn contains(&self, x: i64) -> bool {
+        self.start <= x && x <= self.end
+    }
+
+    /// Sorts the intervals and joins every pair that overlaps or touches.
+    fn merge(mut intervals: Vec<Interval>) -> Vec<Interval> {
+        intervals.sort_unstable();
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end + 1 => {
+                    last.end = last.end.max(interval.end)
+                }
+                _ => merged.push(interval),
+            }
+        }
+        merged
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Sensor {
+    position: Point,
+    beacon: Point,
+    radius: i64,
+}
+
+impl FromStr for Sensor {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(|c: char| !(c.is_ascii_digit() || c == '-'))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse().map_err(|_| ()))
+            .collect::<Result<Vec<i64>, _>>()?;
+        let [sx, sy, bx, by] = values[..] else {
+            return Err(());
+        };
+
+        let position = Point { x: sx, y: sy };
+        let beacon = Point { x: bx, y: by };
+        Ok(Sensor {
+            position,
+            beacon,
+            radius: position.distance(&beacon),
+        })
+    }
+}
+
+impl Sensor {
+    /// The slice of this sensor's diamond that lies on row `y`, if any.
+    fn coverage(&self, y: i64) -> Option<Interval> {
+        let half_width = self.radius - (self.position.y - y).abs();
+        (half_width >= 0).then_some(Interval {
+            start: self.position.x - half_width,
+            end: self.position.x + half_width,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Sensors(Vec<Sensor>);
+
+impl FromStr for Sensors {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Sensors(
+            s.lines().map(|l| l.parse()).collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+impl Sensors {
+    fn coverage(&self, y: i64) -> Vec<Interval> {
+        Interval::merge(self.0.iter().filter_map(|s| s.coverage(y)).collect())
+    }
+
+    /// Counts the positions on row `y` where no beacon can be present.
+    pub fn excluded(&self, y: i64) -> i64 {
+        let coverage = self.coverage(y);
+        let mut beacons = self
+            .0
+            .iter()
+            .filter_map(|s| (s.beacon.y == y).then_some(s.beacon.x))
+            .filter(|x| coverage.iter().any(|i| i.contains(*x)))
+            .collect::<Vec<_>>();
+        beacons.sort_unstable();
+        beacons.dedup();
+
+        coverage.iter().map(Interval::len).sum::<i64>() - beacons.len() as i64
+    }
+
+    /// Finds the only position in the `0..=limit` square that no sensor
+    /// covers. Each row is reduced to a handful of merged intervals, so only
+    /// the rows are scanned rather than every cell.
+    pub fn distress_beacon(&self, limit: i64) -> Option<Point> {
+        let bounds = Interval {
+            start: 0,
+            end: limit,
+        };
+        (0..=limit).find_map(|y| {
+            let mut x = bounds.start;
+            for interval in self.coverage(y) {
+                if interval.start > x {
+                    break;
+                }
+                x = x.max(interval.end + 1);
+            }
+            bounds.contains(x).then_some(Point { x, y })
+        })
+    }
+}
+
+fn tuning_frequency(p: Point) -> i64 {
+    p.x * 4_000_000 + p.y
+}
+
+fn main() {
+    let opt = Opt::parse();
+    let sensors = read_input::<Sensors>("d15/input.txt");
+
+    output!(
+        sensors.excluded(opt.row),
+        tuning_frequency(sensors.distress_beacon(opt.limit).unwrap())
+    );
+}
@@ -0,0 +1,218 @@
+use aoc::*;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+const START: &str = "AA";
+
+#[derive(Debug, Clone)]
+struct Valve {
+    name: String,
+    rate: u32,
+    tunnels: Vec<String>,
+}
+
+impl FromStr for Valve {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+
+        Ok(Valve {
+            name: words.get(1).ok_or(())?.to_string(),
+            rate: words
+                .get(4)
+                .ok_or(())?
+                .trim_start_matches("rate=")
+                .trim_end_matches(';')
+                .parse()
+                .map_err(|_| ())?,
+            tunnels: words
+                .iter()
+                .skip(9)
+                .map(|w| w.trim_end_matches(',').to_string())
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Plan {
+    pressure: u32,
+    openings: Vec<(String, u32)>,
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let openings: Vec<String> = self
+            .openings
+            .iter()
+            .map(|(name, minute)| format!("{name}@{minute}"))
+            .collect();
+        write!(f, "{} [{}]", self.pressure, openings.join(" "))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Team(Plan, Plan);
+
+impl fmt::Display for Team {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (you: {}, elephant: {})",
+            self.0.pressure + self.1.pressure,
+            self.0,
+            self.1
+        )
+    }
+}
+
+/// The valve graph reduced to the start valve and every valve worth opening,
+/// with the shortest travel time between each pair of them.
+#[derive(Debug, Clone)]
+struct Network {
+    valves: Vec<Valve>,
+    distances: Vec<Vec<u32>>,
+    start: usize,
+}
+
+impl FromStr for Network {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valves = s
+            .lines()
+            .map(|l| l.parse())
+            .collect::<Result<Vec<Valve>, _>>()?;
+        let index: HashMap<&str, usize> = valves
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.name.as_str(), i))
+            .collect();
+
+        // Floyd–Warshall over the full graph.
+        let n = valves.len();
+        let mut distances = vec![vec![u32::MAX / 2; n]; n];
+        for (i, valve) in valves.iter().enumerate() {
+            distances[i][i] = 0;
+            for tunnel in valve.tunnels.iter() {
+                distances[i][*index.get(tunnel.as_str()).ok_or(())?] = 1;
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    distances[i][j] = distances[i][j].min(distances[i][k] + distances[k][j]);
+                }
+            }
+        }
+
+        let keep: Vec<usize> = (0..n)
+            .filter(|&i| valves[i].rate > 0 || valves[i].name == START)
+            .collect();
+
+        Ok(Network {
+            distances: keep
+                .iter()
+                .map(|&i| keep.iter().map(|&j| distances[i][j]).collect())
+                .collect(),
+            start: keep
+                .iter()
+                .position(|&i| valves[i].name == START)
+                .ok_or(())?,
+            valves: keep.into_iter().map(|i| valves[i].clone()).collect(),
+        })
+    }
+}
+
+impl Network {
+    /// Returns, for every set of opened valves (as a bitmask), the best plan
+    /// that opens exactly that set within `budget` minutes.
+    fn plans(&self, budget: u32) -> HashMap<u64, Plan> {
+        let mut best = HashMap::new();
+        self.explore(self.start, budget, budget, 0, 0, &mut vec![], &mut best);
+        best
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn explore(
+        &self,
+        at: usize,
+        budget: u32,
+        time: u32,
+        opened: u64,
+        pressure: u32,
+        order: &mut Vec<(usize, u32)>,
+        best: &mut HashMap<u64, Plan>,
+    ) {
+        let plan = best.entry(opened).or_default();
+        if pressure > plan.pressure {
+            *plan = Plan {
+                pressure,
+                openings: order
+                    .iter()
+                    .map(|&(i, minute)| (self.valves[i].name.clone(), minute))
+                    .collect(),
+            };
+        }
+
+        for (next, valve) in self.valves.iter().enumerate() {
+            let cost = self.distances[at][next] + 1;
+            if valve.rate == 0 || opened & 1 << next != 0 || cost >= time {
+                continue;
+            }
+            let remaining = time - cost;
+            order.push((next, budget - remaining));
+            self.explore(
+                next,
+                budget,
+                remaining,
+                opened | 1 << next,
+                pressure + valve.rate * remaining,
+                order,
+                best,
+            );
+            order.pop();
+        }
+    }
+
+    pub fn alone(&self, budget: u32) -> Plan {
+        self.plans(budget)
+            .into_values()
+            .max_by_key(|p| p.pressure)
+            .unwrap_or_default()
+    }
+
+    pub fn with_elephant(&self, budget: u32) -> Team {
+        let mut plans: Vec<(u64, Plan)> = self.plans(budget).into_iter().collect();
+        plans.sort_unstable_by_key(|(_, plan)| Reverse(plan.pressure));
+
+        let mut best: Option<(usize, usize)> = None;
+        let mut max = 0;
+        for (i, (mine, plan)) in plans.iter().enumerate() {
+            if plan.pressure * 2 < max {
+                break;
+            }
+            for (j, (theirs, other)) in plans.iter().enumerate().skip(i) {
+                if plan.pressure + other.pressure <= max {
+                    break;
+                }
+                if mine & theirs == 0 {
+                    max = plan.pressure + other.pressure;
+                    best = Some((i, j));
+                }
+            }
+        }
+
+        let (i, j) = best.unwrap_or_default();
+        Team(plans[i].1.clone(), plans[j].1.clone())
+    }
+}
+
+fn main() {
+    let network = read_input::<Network>("d16/input.txt");
+
+    output!(network.alone(30), network.with_elephant(26));
+}
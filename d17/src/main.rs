@@ -0,0 +1,161 @@
+use aoc::*;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const WIDTH: usize = 7;
+
+/// The five rock shapes, bottom row first, with bit 6 as the leftmost column
+/// and already shifted two units away from the left wall.
+const ROCKS: [&[u8]; 5] = [
+    &[0b0011110],
+    &[0b0001000, 0b0011100, 0b0001000],
+    &[0b0011100, 0b0000100, 0b0000100],
+    &[0b0010000, 0b0010000, 0b0010000, 0b0010000],
+    &[0b0011000, 0b0011000],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Jet {
+    Left,
+    Right,
+}
+
+impl Jet {
+    fn push(&self, rock: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Jet::Left if rock.iter().all(|r| r & 1 << (WIDTH - 1) == 0) => {
+                Some(rock.iter().map(|r| r << 1).collect())
+            }
+            Jet::Right if rock.iter().all(|r| r & 1 == 0) => {
+                Some(rock.iter().map(|r| r >> 1).collect())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Jets(Vec<Jet>);
+
+impl FromStr for Jets {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Jets(
+            s.trim()
+                .chars()
+                .map(|c| match c {
+                    '<' => Ok(Jet::Left),
+                    '>' => Ok(Jet::Right),
+                    _ => Err(()),
+                })
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Chamber<'a> {
+    rows: Vec<u8>,
+    jets: &'a [Jet],
+    jet: usize,
+    rocks: usize,
+}
+
+impl<'a> Chamber<'a> {
+    pub fn new(jets: &'a Jets) -> Self {
+        Chamber {
+            rows: vec![],
+            jets: &jets.0,
+            jet: 0,
+            rocks: 0,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn collides(&self, rock: &[u8], y: usize) -> bool {
+        rock.iter()
+            .enumerate()
+            .any(|(i, r)| self.rows.get(y + i).is_some_and(|row| row & r != 0))
+    }
+
+    pub fn drop_rock(&mut self) {
+        let mut rock = ROCKS[self.rocks % ROCKS.len()].to_vec();
+        let mut y = self.height() + 3;
+
+        loop {
+            let jet = self.jets[self.jet];
+            self.jet = (self.jet + 1) % self.jets.len();
+            if let Some(pushed) = jet.push(&rock) {
+                if !self.collides(&pushed, y) {
+                    rock = pushed;
+                }
+            }
+
+            if y == 0 || self.collides(&rock, y - 1) {
+                break;
+            }
+            y -= 1;
+        }
+
+        if self.rows.len() < y + rock.len() {
+            self.rows.resize(y + rock.len(), 0);
+        }
+        for (i, r) in rock.iter().enumerate() {
+            self.rows[y + i] |= r;
+        }
+        self.rocks += 1;
+    }
+
+    /// The depth below the top of the tower of the highest rock in each
+    /// column.
+    fn profile(&self) -> [usize; WIDTH] {
+        let mut profile = [self.height(); WIDTH];
+        for (column, depth) in profile.iter_mut().enumerate() {
+            if let Some(d) = self
+                .rows
+                .iter()
+                .rev()
+                .position(|row| row & 1 << column != 0)
+            {
+                *depth = d;
+            }
+        }
+        profile
+    }
+}
+
+/// Simulates `n` rocks, skipping ahead as soon as the chamber returns to a
+/// previously seen (rock, jet, profile) state.
+fn height_after(jets: &Jets, n: usize) -> usize {
+    let mut chamber = Chamber::new(jets);
+    let mut seen = HashMap::new();
+    let mut skipped = 0;
+
+    while chamber.rocks < n {
+        chamber.drop_rock();
+        if skipped == 0 {
+            let state = (chamber.rocks % ROCKS.len(), chamber.jet, chamber.profile());
+            if let Some((rocks, height)) = seen.insert(state, (chamber.rocks, chamber.height())) {
+                let period = chamber.rocks - rocks;
+                let cycles = (n - chamber.rocks) / period;
+                skipped = cycles * (chamber.height() - height);
+                chamber.rocks += cycles * period;
+            }
+        }
+    }
+
+    chamber.height() + skipped
+}
+
+fn main() {
+    let jets = read_input::<Jets>("d17/input.txt");
+
+    output!(
+        height_after(&jets, 2022),
+        height_after(&jets, 1_000_000_000_000)
+    );
+}
@@ -0,0 +1,62 @@
+use aoc::*;
+use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+struct Droplet {
+    cubes: HashSet<Point3>,
+}
+
+impl FromStr for Droplet {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Droplet {
+            cubes: s.lines().map(|l| l.parse()).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl Droplet {
+    pub fn surface_area(&self) -> usize {
+        self.cubes
+            .iter()
+            .flat_map(|c| c.neighbours())
+            .filter(|n| !self.cubes.contains(n))
+            .count()
+    }
+
+    /// Counts only the faces reachable from outside by flood filling the air
+    /// in a bounding box one unit larger than the droplet on every side.
+    pub fn exterior_surface_area(&self) -> usize {
+        let Some(first) = self.cubes.iter().next() else {
+            return 0;
+        };
+        let one = Point3::new(1, 1, 1);
+        let (min, max) = self
+            .cubes
+            .iter()
+            .fold((*first, *first), |(min, max), c| (min.min(*c), max.max(*c)));
+        let (min, max) = (min - one, max + one);
+
+        let mut faces = 0;
+        let mut outside = HashSet::from([min]);
+        let mut queue = VecDeque::from([min]);
+        while let Some(air) = queue.pop_front() {
+            for n in air.neighbours().filter(|n| n.within(&min, &max)) {
+                if self.cubes.contains(&n) {
+                    faces += 1;
+                } else if outside.insert(n) {
+                    queue.push_back(n);
+                }
+            }
+        }
+        faces
+    }
+}
+
+fn main() {
+    let droplet = read_input::<Droplet>("d18/input.txt");
+
+    output!(droplet.surface_area(), droplet.exterior_surface_area());
+}
@@ -0,0 +1,214 @@
+use aoc::*;
+use clap::Parser;
+use std::str::FromStr;
+
+const ORE: usize = 0;
+const CLAY: usize = 1;
+const OBSIDIAN: usize = 2;
+const GEODE: usize = 3;
+
+#[derive(Parser, Debug)]
+#[clap(name = "d19", about = "Not Enough Minerals")]
+struct Opt {
+    /// allow building more robots of a kind than any recipe can spend per minute
+    #[clap(long)]
+    no_robot_cap: bool,
+
+    /// keep exploring branches that cannot beat the best result so far
+    #[clap(long)]
+    no_upper_bound: bool,
+
+    /// allow building non-geode robots too late to ever help crack a geode
+    #[clap(long)]
+    no_endgame: bool,
+
+    /// print the number of search nodes visited per blueprint
+    #[clap(long)]
+    stats: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pruning {
+    robot_cap: bool,
+    upper_bound: bool,
+    endgame: bool,
+}
+
+impl From<&Opt> for Pruning {
+    fn from(opt: &Opt) -> Self {
+        Pruning {
+            robot_cap: !opt.no_robot_cap,
+            upper_bound: !opt.no_upper_bound,
+            endgame: !opt.no_endgame,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Blueprint {
+    id: u32,
+    costs: [[u32; 4]; 4],
+}
+
+impl FromStr for Blueprint {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse().map_err(|_| ()))
+            .collect::<Result<Vec<u32>, _>>()?;
+        let [id, ore, clay, obsidian_ore, obsidian_clay, geode_ore, geode_obsidian] = values[..]
+        else {
+            return Err(());
+        };
+
+        Ok(Blueprint {
+            id,
+            costs: [
+                [ore, 0, 0, 0],
+                [clay, 0, 0, 0],
+                [obsidian_ore, obsidian_clay, 0, 0],
+                [geode_ore, 0, geode_obsidian, 0],
+            ],
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Blueprints(Vec<Blueprint>);
+
+impl FromStr for Blueprints {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Blueprints(
+            s.split("Blueprint")
+                .filter(|b| !b.trim().is_empty())
+                .map(|b| b.parse())
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Outcome {
+    geodes: u32,
+    nodes: usize,
+}
+
+struct Search<'a> {
+    blueprint: &'a Blueprint,
+    pruning: Pruning,
+    max_robots: [u32; 4],
+    best: u32,
+    nodes: usize,
+}
+
+impl<'a> Search<'a> {
+    fn new(blueprint: &'a Blueprint, pruning: Pruning) -> Self {
+        let mut max_robots = [u32::MAX; 4];
+        for resource in [ORE, CLAY, OBSIDIAN] {
+            max_robots[resource] = blueprint.costs.iter().map(|c| c[resource]).max().unwrap();
+        }
+
+        Search {
+            blueprint,
+            pruning,
+            max_robots,
+            best: 0,
+            nodes: 0,
+        }
+    }
+
+    /// Branches on which robot to build next, fast-forwarding the clock to the
+    /// minute it becomes affordable.
+    fn explore(&mut self, time: u32, robots: [u32; 4], resources: [u32; 4]) {
+        self.nodes += 1;
+        self.best = self.best.max(resources[GEODE] + robots[GEODE] * time);
+
+        if self.pruning.upper_bound
+            && resources[GEODE] + robots[GEODE] * time + time * time.saturating_sub(1) / 2
+                <= self.best
+        {
+            return;
+        }
+
+        for robot in (ORE..=GEODE).rev() {
+            if self.pruning.robot_cap && robots[robot] >= self.max_robots[robot] {
+                continue;
+            }
+
+            let cost = self.blueprint.costs[robot];
+            let Some(wait) = (ORE..GEODE)
+                .filter(|&r| cost[r] > resources[r])
+                .map(|r| (robots[r] > 0).then(|| (cost[r] - resources[r]).div_ceil(robots[r])))
+                .try_fold(0, |wait, w| w.map(|w| wait.max(w)))
+            else {
+                continue;
+            };
+            if wait + 1 >= time {
+                continue;
+            }
+
+            let remaining = time - wait - 1;
+            if self.pruning.endgame && robot != GEODE && remaining < 3 {
+                continue;
+            }
+
+            let mut next_robots = robots;
+            next_robots[robot] += 1;
+            let mut next_resources = resources;
+            for r in ORE..=GEODE {
+                next_resources[r] += robots[r] * (wait + 1);
+                next_resources[r] -= cost[r];
+            }
+            self.explore(remaining, next_robots, next_resources);
+        }
+    }
+}
+
+impl Blueprint {
+    pub fn max_geodes(&self, time: u32, pruning: Pruning) -> Outcome {
+        let mut search = Search::new(self, pruning);
+        search.explore(time, [1, 0, 0, 0], [0; 4]);
+        Outcome {
+            geodes: search.best,
+            nodes: search.nodes,
+        }
+    }
+}
+
+fn main() {
+    let opt = Opt::parse();
+    let pruning = Pruning::from(&opt);
+    let blueprints = read_input::<Blueprints>("d19/input.txt");
+
+    let solve = |blueprints: &[Blueprint], time| {
+        blueprints
+            .iter()
+            .map(|b| {
+                let outcome = b.max_geodes(time, pruning);
+                if opt.stats {
+                    println!(
+                        "Blueprint {} ({time} minutes): {} geodes, {} nodes",
+                        b.id, outcome.geodes, outcome.nodes
+                    );
+                }
+                (b.id, outcome)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    output!(
+        solve(&blueprints.0, 24)
+            .iter()
+            .map(|(id, o)| id * o.geodes)
+            .sum::<u32>(),
+        solve(&blueprints.0[..blueprints.0.len().min(3)], 32)
+            .iter()
+            .map(|(_, o)| o.geodes)
+            .product::<u32>()
+    );
+}
@@ -0,0 +1,162 @@
+use aoc::*;
+use std::str::FromStr;
+
+const DECRYPTION_KEY: i64 = 811_589_153;
+const COORDINATES: [usize; 3] = [1000, 2000, 3000];
+
+#[derive(Debug, Clone)]
+struct File(Vec<i64>);
+
+impl FromStr for File {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(File(
+            s.lines()
+                .map(|l| l.trim().parse().map_err(|_| ()))
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+/// A circular list of the file's entries, kept as a sequence of small blocks
+/// so that finding, removing and inserting an entry costs O(√n) instead of
+/// shifting the whole list. Entries are referred to by their original index,
+/// which keeps duplicate values apart.
+#[derive(Debug, Clone)]
+struct Mixer {
+    values: Vec<i64>,
+    blocks: Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+    block_size: usize,
+}
+
+impl Mixer {
+    pub fn new(values: Vec<i64>) -> Self {
+        let block_size = ((values.len() as f64).sqrt() as usize).max(1);
+        let mut mixer = Mixer {
+            blocks: vec![(0..values.len()).collect()],
+            block_of: vec![0; values.len()],
+            values,
+            block_size,
+        };
+        mixer.rebuild();
+        mixer
+    }
+
+    fn rebuild(&mut self) {
+        let order: Vec<usize> = self.blocks.concat();
+        self.blocks = order
+            .chunks(self.block_size)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+        for (b, block) in self.blocks.iter().enumerate() {
+            for &entry in block {
+                self.block_of[entry] = b;
+            }
+        }
+    }
+
+    fn position(&self, entry: usize) -> usize {
+        let b = self.block_of[entry];
+        self.blocks[..b].iter().map(Vec::len).sum::<usize>()
+            + self.blocks[b].iter().position(|&e| e == entry).unwrap()
+    }
+
+    fn remove(&mut self, entry: usize) -> usize {
+        let position = self.position(entry);
+        let block = &mut self.blocks[self.block_of[entry]];
+        block.retain(|&e| e != entry);
+        position
+    }
+
+    fn insert(&mut self, entry: usize, mut position: usize) {
+        let last = self.blocks.len() - 1;
+        for (b, block) in self.blocks.iter_mut().enumerate() {
+            if position <= block.len() && (position < block.len() || b == last) {
+                block.insert(position, entry);
+                self.block_of[entry] = b;
+                if block.len() > 2 * self.block_size {
+                    self.rebuild();
+                }
+                return;
+            }
+            position -= block.len();
+        }
+    }
+
+    fn get(&self, mut position: usize) -> i64 {
+        for block in self.blocks.iter() {
+            if position < block.len() {
+                return self.values[block[position]];
+            }
+            position -= block.len();
+        }
+        unreachable!()
+    }
+
+    /// Moves every entry once, in original order, by its own value.
+    pub fn mix(&mut self) {
+        // with one entry or none there is nowhere to move to
+        if self.values.len() <= 1 {
+            return;
+        }
+        let len = self.values.len() as i64;
+        for entry in 0..self.values.len() {
+            let from = self.remove(entry) as i64;
+            let to = (from + self.values[entry]).rem_euclid(len - 1);
+            self.insert(entry, to as usize);
+        }
+    }
+
+    /// The sum of the values the given offsets after the `0`, or `None` if
+    /// there is no `0`.
+    pub fn grove_coordinates(&self) -> Option<i64> {
+        let zero = self.values.iter().position(|&v| v == 0)?;
+        let start = self.position(zero);
+        Some(
+            COORDINATES
+                .iter()
+                .map(|offset| self.get((start + offset) % self.values.len()))
+                .sum(),
+        )
+    }
+}
+
+fn decrypt(file: &File, key: i64, rounds: usize) -> Option<i64> {
+    let mut mixer = Mixer::new(file.0.iter().map(|v| v * key).collect());
+    for _ in 0..rounds {
+        mixer.mix();
+    }
+    mixer.grove_coordinates()
+}
+
+fn main() {
+    let file = read_input::<File>("d20/input.txt");
+
+    let answer = |key, rounds| {
+        decrypt(&file, key, rounds).map_or("no 0 in the file".to_string(), |c| c.to_string())
+    };
+
+    output!(answer(1, 1), answer(DECRYPTION_KEY, 10));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let file = File(vec![1, 2, -3, 3, -2, 0, 4]);
+        assert_eq!(decrypt(&file, 1, 1), Some(3));
+        assert_eq!(decrypt(&file, DECRYPTION_KEY, 10), Some(1623178306));
+    }
+
+    #[test]
+    fn degenerate_files() {
+        assert_eq!(decrypt(&File(vec![0]), DECRYPTION_KEY, 10), Some(0));
+        assert_eq!(decrypt(&File(vec![5]), 1, 1), None);
+        assert_eq!(decrypt(&File(vec![]), 1, 1), None);
+        assert_eq!(decrypt(&File(vec![3, -1]), 1, 1), None);
+    }
+}
@@ -0,0 +1,267 @@
+use aoc::*;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const ROOT: &str = "root";
+const HUMAN: &str = "humn";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl FromStr for Operation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "+" => Ok(Operation::Add),
+            "-" => Ok(Operation::Sub),
+            "*" => Ok(Operation::Mul),
+            "/" => Ok(Operation::Div),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Operation {
+    fn apply(&self, a: i64, b: i64) -> i64 {
+        match self {
+            Operation::Add => a + b,
+            Operation::Sub => a - b,
+            Operation::Mul => a * b,
+            Operation::Div => a / b,
+        }
+    }
+
+    /// Solves `x op b == target` for `x`, if an integer solution exists.
+    fn solve_left(&self, target: i64, b: i64) -> Option<i64> {
+        match self {
+            Operation::Add => Some(target - b),
+            Operation::Sub => Some(target + b),
+            Operation::Mul => (b != 0 && target % b == 0).then(|| target / b),
+            Operation::Div => (b != 0).then(|| target.checked_mul(b)).flatten(),
+        }
+    }
+
+    /// Solves `a op x == target` for `x`, if an integer solution exists.
+    ///
+    /// Division truncates, so `a / x == target` can hold even when `target`
+    /// does not divide `a`: `a / target` is then the solution furthest from
+    /// zero, if there is one at all.
+    fn solve_right(&self, target: i64, a: i64) -> Option<i64> {
+        match self {
+            Operation::Add => Some(target - a),
+            Operation::Sub => Some(a - target),
+            Operation::Mul => (a != 0 && target % a == 0).then(|| target / a),
+            Operation::Div if target == 0 => a.checked_abs()?.checked_add(1),
+            Operation::Div => a
+                .checked_div(target)
+                .filter(|&x| x != 0 && a.checked_div(x) == Some(target)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Job {
+    Number(i64),
+    Math(String, Operation, String),
+}
+
+impl FromStr for Job {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_whitespace().collect::<Vec<_>>()[..] {
+            [number] => Ok(Job::Number(number.parse().map_err(|_| ())?)),
+            [a, operation, b] => Ok(Job::Math(a.to_string(), operation.parse()?, b.to_string())),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Monkeys(HashMap<String, Job>);
+
+impl FromStr for Monkeys {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Monkeys(
+            s.lines()
+                .map(|l| {
+                    let (name, job) = l.split_once(": ").ok_or(())?;
+                    Ok((name.to_string(), job.parse()?))
+                })
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+/// What a monkey yells, and whether that depends on what `humn` yells.
+#[derive(Debug, Clone, Copy)]
+struct Yell {
+    value: i64,
+    human: bool,
+}
+
+impl Monkeys {
+    /// Works out every monkey's yell in a single post-order pass over the
+    /// expression DAG, so a shared sub-expression is only evaluated once.
+    fn analyse(&self) -> HashMap<&str, Yell> {
+        let mut yells: HashMap<&str, Yell> = HashMap::with_capacity(self.0.len());
+        let mut stack: Vec<(&str, bool)> = self.0.keys().map(|n| (n.as_str(), false)).collect();
+        while let Some((name, ready)) = stack.pop() {
+            if yells.contains_key(name) {
+                continue;
+            }
+            let yell = match &self.0[name] {
+                Job::Number(n) => Yell {
+                    value: *n,
+                    human: name == HUMAN,
+                },
+                Job::Math(a, operation, b) if ready => {
+                    let (a, b) = (yells[a.as_str()], yells[b.as_str()]);
+                    Yell {
+                        value: operation.apply(a.value, b.value),
+                        human: name == HUMAN || a.human || b.human,
+                    }
+                }
+                Job::Math(a, _, b) => {
+                    stack.extend([(name, true), (a.as_str(), false), (b.as_str(), false)]);
+                    continue;
+                }
+            };
+            yells.insert(name, yell);
+        }
+        yells
+    }
+
+    pub fn evaluate(&self, name: &str) -> i64 {
+        self.analyse()[name].value
+    }
+
+    /// Finds the value `humn` must shout for `name` to evaluate to `target`,
+    /// undoing each operation on the way down from `name` to `humn`.
+    fn solve(&self, yells: &HashMap<&str, Yell>, name: &str, target: i64) -> Option<i64> {
+        if name == HUMAN {
+            return Some(target);
+        }
+        match &self.0[name] {
+            Job::Number(_) => None,
+            Job::Math(a, operation, b) => {
+                let (a, b) = (a.as_str(), b.as_str());
+                // undoing one operation at a time only works with `humn` on
+                // a single side
+                if yells[a].human && yells[b].human {
+                    return None;
+                }
+                if yells[a].human {
+                    self.solve(yells, a, operation.solve_left(target, yells[b].value)?)
+                } else {
+                    self.solve(yells, b, operation.solve_right(target, yells[a].value)?)
+                }
+            }
+        }
+    }
+
+    /// Treats `root` as an equality check and returns the value `humn` must
+    /// shout for both sides to match.
+    pub fn solve_human(&self) -> Option<i64> {
+        let Job::Math(a, _, b) = &self.0[ROOT] else {
+            return None;
+        };
+        let yells = self.analyse();
+        let (a, b) = (a.as_str(), b.as_str());
+        if yells[a].human && yells[b].human {
+            return None;
+        }
+        if yells[a].human {
+            self.solve(&yells, a, yells[b].value)
+        } else {
+            self.solve(&yells, b, yells[a].value)
+        }
+    }
+}
+
+fn main() {
+    let monkeys = read_input::<Monkeys>("d21/input.txt");
+
+    output!(monkeys.evaluate(ROOT), monkeys.solve_human().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32";
+
+    #[test]
+    fn example() {
+        let monkeys: Monkeys = EXAMPLE.parse().unwrap();
+        assert_eq!(monkeys.evaluate(ROOT), 152);
+        assert_eq!(monkeys.solve_human(), Some(301));
+    }
+
+    #[test]
+    fn truncating_division() {
+        for a in -30..=30 {
+            for target in -30..=30 {
+                let solvable = (-40..=40).any(|x| x != 0 && a / x == target);
+                match Operation::Div.solve_right(target, a) {
+                    Some(x) => assert_eq!(a / x, target, "{a} / {x}"),
+                    None => assert!(!solvable, "{a} / x == {target}"),
+                }
+                if let Some(x) = Operation::Div.solve_left(target, a) {
+                    assert_eq!(x / a, target, "{x} / {a}");
+                }
+            }
+        }
+        assert_eq!(Operation::Div.solve_right(2, 7), Some(3));
+
+        let monkeys: Monkeys = "root: seven + two\nseven: a / humn\na: 7\ntwo: 2\nhumn: 1"
+            .parse()
+            .unwrap();
+        assert_eq!(monkeys.solve_human(), Some(3));
+    }
+
+    #[test]
+    fn shared_subexpressions() {
+        // each level uses the one below twice, so 2^60 paths lead down to `one`
+        let mut lines = vec!["one: 1".to_string(), "m0: one * one".to_string()];
+        for level in 1..=60 {
+            lines.push(format!("m{level}: m{} + m{}", level - 1, level - 1));
+        }
+        lines.push("left: humn + m60".to_string());
+        lines.push("root: left + goal".to_string());
+        lines.push(format!("goal: {}", 1i64 << 61));
+        lines.push("humn: 1".to_string());
+        let monkeys: Monkeys = lines.join("\n").parse().unwrap();
+
+        assert_eq!(monkeys.evaluate(ROOT), 1 + (1 << 60) + (1 << 61));
+        assert_eq!(monkeys.solve_human(), Some(1 << 60));
+
+        // `humn` on both sides of an operation cannot be undone step by step
+        let monkeys: Monkeys = "root: twice + four\ntwice: humn + humn\nfour: 4\nhumn: 1"
+            .parse()
+            .unwrap();
+        assert_eq!(monkeys.solve_human(), None);
+    }
+}
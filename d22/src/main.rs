@@ -0,0 +1,371 @@
+use aoc::*;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Facing {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
+impl Facing {
+    const ALL: [Facing; 4] = [Facing::Right, Facing::Down, Facing::Left, Facing::Up];
+
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Facing::Right => (0, 1),
+            Facing::Down => (1, 0),
+            Facing::Left => (0, -1),
+            Facing::Up => (-1, 0),
+        }
+    }
+
+    fn turn(&self, step: &Step) -> Facing {
+        let index = *self as usize;
+        match step {
+            Step::Left => Self::ALL[(index + 3) % 4],
+            Step::Right => Self::ALL[(index + 1) % 4],
+            Step::Forward(_) => *self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Forward(usize),
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone)]
+struct Path(Vec<Step>);
+
+impl FromStr for Path {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = vec![];
+        let mut distance = None;
+        for c in s.trim().chars() {
+            if let Some(digit) = c.to_digit(10) {
+                distance = Some(distance.unwrap_or(0) * 10 + digit as usize);
+                continue;
+            }
+            if let Some(d) = distance.take() {
+                steps.push(Step::Forward(d));
+            }
+            steps.push(match c {
+                'L' => Step::Left,
+                'R' => Step::Right,
+                _ => return Err(()),
+            });
+        }
+        steps.extend(distance.map(Step::Forward));
+        Ok(Path(steps))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Void,
+    Open,
+    Wall,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wrapping {
+    Flat,
+    Cube,
+}
+
+/// The orientation of one face of the folded cube: its outward normal and
+/// the directions its columns and rows run in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Face {
+    normal: Point3,
+    right: Point3,
+    down: Point3,
+}
+
+impl Face {
+    fn axis(&self, facing: Facing) -> Point3 {
+        match facing {
+            Facing::Right => self.right,
+            Facing::Down => self.down,
+            Facing::Left => -self.right,
+            Facing::Up => -self.down,
+        }
+    }
+
+    fn facing(&self, axis: Point3) -> Option<Facing> {
+        Facing::ALL.into_iter().find(|f| self.axis(*f) == axis)
+    }
+
+    /// The face reached by rolling the cube over the edge towards `facing`.
+    fn neighbour(&self, facing: Facing) -> Face {
+        let axis = self.axis(facing);
+        let roll = |v: Point3| match v {
+            v if v == axis => -self.normal,
+            v if v == -axis => self.normal,
+            v => v,
+        };
+        Face {
+            normal: axis,
+            right: roll(self.right),
+            down: roll(self.down),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Board {
+    tiles: Vec<Vec<Tile>>,
+    size: usize,
+    faces: HashMap<(usize, usize), Face>,
+}
+
+impl FromStr for Board {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let width = s.lines().map(str::len).max().ok_or(())?;
+        let tiles: Vec<Vec<Tile>> = s
+            .lines()
+            .map(|l| {
+                l.chars()
+                    .chain(std::iter::repeat(' '))
+                    .take(width)
+                    .map(|c| match c {
+                        ' ' => Ok(Tile::Void),
+                        '.' => Ok(Tile::Open),
+                        '#' => Ok(Tile::Wall),
+                        _ => Err(()),
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        let area = tiles.iter().flatten().filter(|t| **t != Tile::Void).count() / 6;
+        let size = (1..=area).find(|n| n * n >= area).ok_or(())?;
+        if size * size != area {
+            return Err(());
+        }
+
+        let mut board = Board {
+            tiles,
+            size,
+            faces: HashMap::new(),
+        };
+        board.fold()?;
+        Ok(board)
+    }
+}
+
+impl Board {
+    fn tile(&self, row: isize, column: isize) -> Tile {
+        if row < 0 || column < 0 {
+            return Tile::Void;
+        }
+        self.tiles
+            .get(row as usize)
+            .and_then(|r| r.get(column as usize))
+            .copied()
+            .unwrap_or(Tile::Void)
+    }
+
+    /// Folds the net into a cube by walking from face to face across shared
+    /// edges, so any of the eleven cube nets is supported. Fails unless the
+    /// faces are whole and fold onto six different sides of the cube.
+    fn fold(&mut self) -> Result<(), ()> {
+        let size = self.size as isize;
+        let start = (0..self.tiles.len() / self.size)
+            .flat_map(|r| (0..self.tiles[0].len() / self.size).map(move |c| (r, c)))
+            .find(|&(r, c)| self.tile(r as isize * size, c as isize * size) != Tile::Void)
+            .ok_or(())?;
+
+        let mut faces = HashMap::from([(
+            start,
+            Face {
+                normal: Point3::new(0, 0, 1),
+                right: Point3::new(1, 0, 0),
+                down: Point3::new(0, 1, 0),
+            },
+        )]);
+        let mut queue = VecDeque::from([start]);
+        while let Some((r, c)) = queue.pop_front() {
+            let face = faces[&(r, c)];
+            for facing in Facing::ALL {
+                let (dr, dc) = facing.delta();
+                let (nr, nc) = (r as isize + dr, c as isize + dc);
+                if self.tile(nr * size, nc * size) == Tile::Void {
+                    continue;
+                }
+                let next = (nr as usize, nc as usize);
+                if let Entry::Vacant(entry) = faces.entry(next) {
+                    entry.insert(face.neighbour(facing));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let whole = faces.keys().all(|&(r, c)| {
+            (0..size).all(|i| {
+                (0..size)
+                    .all(|j| self.tile(r as isize * size + i, c as isize * size + j) != Tile::Void)
+            })
+        });
+        let normals: HashSet<Point3> = faces.values().map(|f| f.normal).collect();
+        if !whole || faces.len() != 6 || normals.len() != 6 {
+            return Err(());
+        }
+        self.faces = faces;
+        Ok(())
+    }
+
+    fn wrap_flat(&self, (row, column): (usize, usize), facing: Facing) -> ((usize, usize), Facing) {
+        let (dr, dc) = facing.delta();
+        let (mut r, mut c) = (row as isize, column as isize);
+        while self.tile(r - dr, c - dc) != Tile::Void {
+            (r, c) = (r - dr, c - dc);
+        }
+        ((r as usize, c as usize), facing)
+    }
+
+    /// Carries a position over the edge of its face onto the adjacent face of
+    /// the cube, using coordinates on a cube of side `2 * size` centred at the
+    /// origin.
+    fn wrap_cube(&self, (row, column): (usize, usize), facing: Facing) -> ((usize, usize), Facing) {
+        let n = self.size as i32;
+        let face = self.faces[&(row / self.size, column / self.size)];
+        let (i, j) = ((row % self.size) as i32, (column % self.size) as i32);
+        let axis = face.axis(facing);
+
+        let position =
+            face.normal * n + face.right * (2 * j + 1 - n) + face.down * (2 * i + 1 - n) + axis
+                - face.normal;
+        let (&(fr, fc), next) = self.faces.iter().find(|(_, f)| f.normal == axis).unwrap();
+        let i = ((position.dot(&next.down) + n - 1) / 2) as usize;
+        let j = ((position.dot(&next.right) + n - 1) / 2) as usize;
+
+        (
+            (fr * self.size + i, fc * self.size + j),
+            next.facing(-face.normal).unwrap(),
+        )
+    }
+
+    fn step(
+        &self,
+        (row, column): (usize, usize),
+        facing: Facing,
+        wrapping: Wrapping,
+    ) -> Option<((usize, usize), Facing)> {
+        let (dr, dc) = facing.delta();
+        let (r, c) = (row as isize + dr, column as isize + dc);
+        let next = match (self.tile(r, c), wrapping) {
+            (Tile::Void, Wrapping::Flat) => self.wrap_flat((row, column), facing),
+            (Tile::Void, Wrapping::Cube) => self.wrap_cube((row, column), facing),
+            _ => ((r as usize, c as usize), facing),
+        };
+        (self.tiles[next.0 .0][next.0 .1] == Tile::Open).then_some(next)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Notes {
+    board: Board,
+    path: Path,
+}
+
+impl FromStr for Notes {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (board, path) = s.split_once(MULTILINE).ok_or(())?;
+        Ok(Notes {
+            board: board.parse()?,
+            path: path.parse()?,
+        })
+    }
+}
+
+impl Notes {
+    pub fn password(&self, wrapping: Wrapping) -> usize {
+        let mut position = (
+            0,
+            self.board.tiles[0]
+                .iter()
+                .position(|t| *t == Tile::Open)
+                .unwrap(),
+        );
+        let mut facing = Facing::Right;
+
+        for step in self.path.0.iter() {
+            match step {
+                Step::Forward(distance) => {
+                    for _ in 0..*distance {
+                        match self.board.step(position, facing, wrapping) {
+                            Some(next) => (position, facing) = next,
+                            None => break,
+                        }
+                    }
+                }
+                turn => facing = facing.turn(turn),
+            }
+        }
+
+        1000 * (position.0 + 1) + 4 * (position.1 + 1) + facing as usize
+    }
+}
+
+fn main() {
+    let notes = read_input::<Notes>("d22/input.txt");
+
+    output!(
+        notes.password(Wrapping::Flat),
+        notes.password(Wrapping::Cube)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5";
+
+    #[test]
+    fn example() {
+        let notes: Notes = EXAMPLE.parse().unwrap();
+        assert_eq!(notes.password(Wrapping::Flat), 6032);
+        assert_eq!(notes.password(Wrapping::Cube), 5031);
+    }
+
+    #[test]
+    fn not_a_cube_net() {
+        assert!(" . \n...\n . \n . ".parse::<Board>().is_ok());
+        // six faces, but a 3x2 block folds two of them onto the same side
+        assert!("...\n...".parse::<Board>().is_err());
+        // six faces, but not all joined up
+        assert!("...\n   \n...".parse::<Board>().is_err());
+        // the right number of tiles, but one face is missing a corner
+        let cross = "  ..  \n  ..  \n......\n......\n  ..  \n  ..  \n  ..  \n  ..  ";
+        assert!(cross.parse::<Board>().is_ok());
+        let torn = format!("{}  . .", &cross[..cross.len() - 6]);
+        assert!(torn.parse::<Board>().is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use aoc::*;
+use std::ops::Add;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+const fn offset(x: i32, y: i32) -> Point {
+    Point { x, y }
+}
+
+const NEIGHBOURS: [Point; 8] = [
+    offset(-1, -1),
+    offset(0, -1),
+    offset(1, -1),
+    offset(-1, 0),
+    offset(1, 0),
+    offset(-1, 1),
+    offset(0, 1),
+    offset(1, 1),
+];
+
+/// The direction to move in, followed by the three positions that have to be
+/// free to propose it, in the initial order of preference.
+const DIRECTIONS: [(Point, [Point; 3]); 4] = [
+    (
+        offset(0, -1),
+        [offset(-1, -1), offset(0, -1), offset(1, -1)],
+    ),
+    (offset(0, 1), [offset(-1, 1), offset(0, 1), offset(1, 1)]),
+    (
+        offset(-1, 0),
+        [offset(-1, -1), offset(-1, 0), offset(-1, 1)],
+    ),
+    (offset(1, 0), [offset(1, -1), offset(1, 0), offset(1, 1)]),
+];
+
+/// Free space kept around the elves whenever the grid has to grow.
+const MARGIN: i32 = 16;
+
+/// The elves on a dense occupancy grid that is regrown whenever an elf gets
+/// close to its edge, which is much faster than hashing every neighbour.
+#[derive(Debug, Clone)]
+struct Grove {
+    elves: Vec<Point>,
+    occupied: Vec<bool>,
+    claims: Vec<u8>,
+    width: i32,
+    round: usize,
+}
+
+impl FromStr for Grove {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut grove = Grove {
+            elves: s
+                .lines()
+                .enumerate()
+                .flat_map(|(y, l)| {
+                    l.chars().enumerate().filter_map(move |(x, c)| {
+                        (c == '#').then_some(Point {
+                            x: x as i32,
+                            y: y as i32,
+                        })
+                    })
+                })
+                .collect(),
+            occupied: vec![],
+            claims: vec![],
+            width: 0,
+            round: 0,
+        };
+        grove.regrow();
+        Ok(grove)
+    }
+}
+
+impl Grove {
+    fn bounds(&self) -> (Point, Point) {
+        self.elves.iter().fold(
+            (offset(i32::MAX, i32::MAX), offset(i32::MIN, i32::MIN)),
+            |(min, max), e| {
+                (
+                    offset(min.x.min(e.x), min.y.min(e.y)),
+                    offset(max.x.max(e.x), max.y.max(e.y)),
+                )
+            },
+        )
+    }
+
+    fn regrow(&mut self) {
+        let (min, max) = self.bounds();
+        let shift = offset(MARGIN - min.x, MARGIN - min.y);
+        self.width = max.x - min.x + 1 + 2 * MARGIN;
+        let height = max.y - min.y + 1 + 2 * MARGIN;
+
+        self.occupied = vec![false; (self.width * height) as usize];
+        self.claims = vec![0; self.occupied.len()];
+        for elf in self.elves.iter_mut() {
+            *elf = *elf + shift;
+        }
+        for i in 0..self.elves.len() {
+            let index = self.index(self.elves[i]);
+            self.occupied[index] = true;
+        }
+    }
+
+    fn index(&self, p: Point) -> usize {
+        (p.y * self.width + p.x) as usize
+    }
+
+    fn is_elf(&self, p: Point) -> bool {
+        self.occupied[self.index(p)]
+    }
+
+    fn propose(&self, elf: Point) -> Option<Point> {
+        if NEIGHBOURS.iter().all(|n| !self.is_elf(elf + *n)) {
+            return None;
+        }
+        (0..DIRECTIONS.len())
+            .map(|i| DIRECTIONS[(self.round + i) % DIRECTIONS.len()])
+            .find(|(_, checks)| checks.iter().all(|c| !self.is_elf(elf + *c)))
+            .map(|(direction, _)| elf + direction)
+    }
+
+    /// Plays a single round and returns how many elves moved.
+    pub fn step(&mut self) -> usize {
+        let height = self.occupied.len() as i32 / self.width;
+        if self
+            .elves
+            .iter()
+            .any(|e| e.x < 2 || e.y < 2 || e.x >= self.width - 2 || e.y >= height - 2)
+        {
+            self.regrow();
+        }
+
+        let proposals: Vec<Option<Point>> = self.elves.iter().map(|e| self.propose(*e)).collect();
+        for destination in proposals.iter().flatten() {
+            let index = self.index(*destination);
+            self.claims[index] = self.claims[index].saturating_add(1);
+        }
+
+        let mut moved = 0;
+        for (i, proposal) in proposals.iter().enumerate() {
+            let Some(destination) = *proposal else {
+                continue;
+            };
+            let (from, to) = (self.index(self.elves[i]), self.index(destination));
+            if self.claims[to] == 1 {
+                self.occupied[from] = false;
+                self.occupied[to] = true;
+                self.elves[i] = destination;
+                moved += 1;
+            }
+        }
+        for destination in proposals.iter().flatten() {
+            let index = self.index(*destination);
+            self.claims[index] = 0;
+        }
+
+        self.round += 1;
+        moved
+    }
+
+    pub fn empty_ground(&self) -> usize {
+        let (min, max) = self.bounds();
+        ((max.x - min.x + 1) * (max.y - min.y + 1)) as usize - self.elves.len()
+    }
+}
+
+fn main() {
+    let mut grove = read_input::<Grove>("d23/input.txt");
+
+    // the elves may settle before the ten rounds part 1 needs are over
+    let mut settled = None;
+    for _ in 0..10 {
+        if grove.step() == 0 && settled.is_none() {
+            settled = Some(grove.round);
+        }
+    }
+    let empty = grove.empty_ground();
+    let settled = settled.unwrap_or_else(|| {
+        while grove.step() > 0 {}
+        grove.round
+    });
+
+    output!(empty, settled);
+}
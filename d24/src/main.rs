@@ -0,0 +1,222 @@
+use aoc::*;
+use clap::Parser;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Parser, Debug)]
+#[clap(name = "d24", about = "Blizzard Basin")]
+struct Opt {
+    /// render the valley for every minute of the there-back-there trip
+    #[clap(short, long)]
+    render: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Point {
+    row: i32,
+    column: i32,
+}
+
+impl Point {
+    fn moves(self) -> [Point; 5] {
+        [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)].map(|(dr, dc)| Point {
+            row: self.row + dr,
+            column: self.column + dc,
+        })
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The path taken by the expedition, one position per minute starting at
+/// `start`.
+#[derive(Debug, Clone)]
+struct Route {
+    start: usize,
+    positions: Vec<Point>,
+}
+
+impl Route {
+    pub fn end(&self) -> usize {
+        self.start + self.positions.len() - 1
+    }
+
+    fn chain(mut self, next: Route) -> Route {
+        self.positions.extend(next.positions.into_iter().skip(1));
+        self
+    }
+}
+
+/// The inside of the valley without its walls; the entrance sits just above
+/// the top row and the exit just below the bottom row.
+#[derive(Debug, Clone)]
+struct Valley {
+    tiles: Vec<Vec<char>>,
+    width: usize,
+    height: usize,
+    entrance: Point,
+    exit: Point,
+}
+
+impl FromStr for Valley {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().collect();
+        if lines.len() < 3 {
+            return Err(());
+        }
+        let tiles: Vec<Vec<char>> = lines[1..lines.len() - 1]
+            .iter()
+            .map(|l| l.chars().skip(1).take(l.len() - 2).collect())
+            .collect();
+        let gap = |l: &str| l.find('.').map(|c| c as i32 - 1).ok_or(());
+
+        Ok(Valley {
+            width: tiles[0].len(),
+            height: tiles.len(),
+            entrance: Point {
+                row: -1,
+                column: gap(lines[0])?,
+            },
+            exit: Point {
+                row: tiles.len() as i32,
+                column: gap(lines[lines.len() - 1])?,
+            },
+            tiles,
+        })
+    }
+}
+
+impl Valley {
+    /// Blizzard positions repeat after this many minutes.
+    pub fn period(&self) -> usize {
+        self.width * self.height / gcd(self.width, self.height)
+    }
+
+    /// The blizzards covering `p` at `time`, found by tracing each of the four
+    /// directions back to where such a blizzard would have started.
+    fn blizzards(&self, p: Point, time: usize) -> impl Iterator<Item = char> + '_ {
+        let (w, h) = (self.width as i32, self.height as i32);
+        let t = time as i32;
+        let (r, c) = (p.row, p.column);
+        [
+            ('>', r, (c - t).rem_euclid(w)),
+            ('<', r, (c + t).rem_euclid(w)),
+            ('v', (r - t).rem_euclid(h), c),
+            ('^', (r + t).rem_euclid(h), c),
+        ]
+        .into_iter()
+        .filter(move |&(b, r, c)| self.tiles[r as usize][c as usize] == b)
+        .map(|(b, _, _)| b)
+    }
+
+    fn is_free(&self, p: Point, time: usize) -> bool {
+        if p == self.entrance || p == self.exit {
+            return true;
+        }
+        (0..self.height as i32).contains(&p.row)
+            && (0..self.width as i32).contains(&p.column)
+            && self.blizzards(p, time).next().is_none()
+    }
+
+    /// Breadth-first search over (position, minute modulo the blizzard
+    /// period), leaving `from` on minute `time`.
+    pub fn route(&self, from: Point, to: Point, time: usize) -> Option<Route> {
+        let period = self.period();
+        let mut seen = HashSet::from([(from, time % period)]);
+        let mut parents: HashMap<(Point, usize), Point> = HashMap::new();
+        let mut frontier = vec![from];
+        let mut minute = time;
+
+        while !frontier.is_empty() {
+            if frontier.contains(&to) {
+                let mut positions = vec![to];
+                while minute > time {
+                    positions.push(parents[&(positions[positions.len() - 1], minute)]);
+                    minute -= 1;
+                }
+                positions.reverse();
+                return Some(Route {
+                    start: time,
+                    positions,
+                });
+            }
+
+            minute += 1;
+            let mut next = vec![];
+            for p in frontier {
+                for m in p.moves() {
+                    if self.is_free(m, minute) && seen.insert((m, minute % period)) {
+                        parents.insert((m, minute), p);
+                        next.push(m);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        None
+    }
+
+    pub fn render(&self, time: usize, expedition: Point) -> String {
+        let mut out = String::new();
+        let border = |out: &mut String, gap: Point| {
+            for column in -1..=self.width as i32 {
+                out.push(match column {
+                    c if c == gap.column && gap == expedition => 'E',
+                    c if c == gap.column => '.',
+                    _ => '#',
+                });
+            }
+            out.push('\n');
+        };
+
+        border(&mut out, self.entrance);
+        for row in 0..self.height as i32 {
+            out.push('#');
+            for column in 0..self.width as i32 {
+                let p = Point { row, column };
+                let blizzards: Vec<char> = self.blizzards(p, time).collect();
+                match blizzards[..] {
+                    _ if p == expedition => out.push('E'),
+                    [] => out.push('.'),
+                    [b] => out.push(b),
+                    _ => write!(out, "{}", blizzards.len()).unwrap(),
+                }
+            }
+            out.push_str("#\n");
+        }
+        border(&mut out, self.exit);
+        out
+    }
+}
+
+fn main() {
+    let opt = Opt::parse();
+    let valley = read_input::<Valley>("d24/input.txt");
+
+    let there = valley.route(valley.entrance, valley.exit, 0).unwrap();
+    let back = valley
+        .route(valley.exit, valley.entrance, there.end())
+        .unwrap();
+    let again = valley
+        .route(valley.entrance, valley.exit, back.end())
+        .unwrap();
+    let first = there.end();
+    let trip = there.chain(back).chain(again);
+
+    if opt.render {
+        for (minute, position) in trip.positions.iter().enumerate() {
+            println!("Minute {minute}:\n{}", valley.render(minute, *position));
+        }
+    }
+
+    output!(first, trip.end());
+}
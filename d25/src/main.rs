@@ -0,0 +1,241 @@
+use aoc::*;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+
+/// A number in balanced base 5, stored as digits in `-2..=2` with the least
+/// significant digit first and no leading zeros.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Snafu {
+    digits: Vec<i8>,
+}
+
+impl Snafu {
+    fn trim(mut self) -> Self {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        self
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(());
+        }
+        Ok(Snafu {
+            digits: s
+                .chars()
+                .rev()
+                .map(|c| match c {
+                    '2' => Ok(2),
+                    '1' => Ok(1),
+                    '0' => Ok(0),
+                    '-' => Ok(-1),
+                    '=' => Ok(-2),
+                    _ => Err(()),
+                })
+                .collect::<Result<_, _>>()?,
+        }
+        .trim())
+    }
+}
+
+impl fmt::Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+        for digit in self.digits.iter().rev() {
+            write!(
+                f,
+                "{}",
+                match digit {
+                    2 => '2',
+                    1 => '1',
+                    0 => '0',
+                    -1 => '-',
+                    _ => '=',
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Add for &Snafu {
+    type Output = Snafu;
+
+    fn add(self, other: &Snafu) -> Snafu {
+        let mut digits = vec![];
+        let mut carry = 0;
+        for i in 0..self.digits.len().max(other.digits.len()) {
+            let sum = self.digits.get(i).unwrap_or(&0) + other.digits.get(i).unwrap_or(&0) + carry;
+            let digit = (sum + 2).rem_euclid(5) - 2;
+            digits.push(digit);
+            carry = (sum - digit) / 5;
+        }
+        if carry != 0 {
+            digits.push(carry);
+        }
+        Snafu { digits }.trim()
+    }
+}
+
+impl Add for Snafu {
+    type Output = Snafu;
+
+    fn add(self, other: Snafu) -> Snafu {
+        &self + &other
+    }
+}
+
+impl<'a> Sum<&'a Self> for Snafu {
+    fn sum<I>(iter: I) -> Self
+    where
+        I: Iterator<Item = &'a Self>,
+    {
+        iter.fold(Snafu::default(), |a, b| &a + b)
+    }
+}
+
+impl From<i128> for Snafu {
+    fn from(mut n: i128) -> Self {
+        let mut digits = vec![];
+        while n != 0 {
+            // borrow from the next place rather than computing `n + 2` or
+            // `n - digit`, either of which overflows at the ends of the range
+            let rem = n.rem_euclid(5);
+            let borrow = rem > 2;
+            digits.push(if borrow { rem - 5 } else { rem } as i8);
+            n = n.div_euclid(5) + borrow as i128;
+        }
+        Snafu { digits }
+    }
+}
+
+impl From<i64> for Snafu {
+    fn from(n: i64) -> Self {
+        Snafu::from(n as i128)
+    }
+}
+
+impl TryFrom<&Snafu> for i128 {
+    type Error = ();
+
+    fn try_from(snafu: &Snafu) -> Result<Self, Self::Error> {
+        // `4n + (n + d)` rather than `5n + d`, as `5n` alone can overflow when
+        // the last digit would bring the result back in range
+        snafu.digits.iter().rev().try_fold(0i128, |n, d| {
+            n.checked_mul(4)
+                .and_then(|m| m.checked_add(n + *d as i128))
+                .ok_or(())
+        })
+    }
+}
+
+impl TryFrom<&Snafu> for i64 {
+    type Error = ();
+
+    fn try_from(snafu: &Snafu) -> Result<Self, Self::Error> {
+        i128::try_from(snafu)?.try_into().map_err(|_| ())
+    }
+}
+
+fn main() {
+    let input = read_input::<String>("d25/input.txt");
+    let numbers: Vec<Snafu> = input.lines().map(|l| l.parse().unwrap()).collect();
+
+    output!(numbers.iter().sum::<Snafu>(), "-");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small xorshift generator so the property checks stay deterministic
+    /// without pulling in a dependency.
+    fn samples(seed: u64, count: usize) -> impl Iterator<Item = i64> {
+        let mut state = seed;
+        (0..count).map(move |_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as i64 >> (state % 63)
+        })
+    }
+
+    fn values() -> impl Iterator<Item = i64> {
+        (-5000..=5000)
+            .chain(samples(0x2545_f491_4f6c_dd1d, 10_000))
+            .chain([i64::MIN, i64::MIN + 1, i64::MAX - 1, i64::MAX])
+    }
+
+    #[test]
+    fn examples() {
+        for (decimal, snafu) in [
+            (1i64, "1"),
+            (3, "1="),
+            (8, "2="),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+            (4890, "2=-1=0"),
+        ] {
+            assert_eq!(Snafu::from(decimal).to_string(), snafu);
+            assert_eq!(i64::try_from(&snafu.parse::<Snafu>().unwrap()), Ok(decimal));
+        }
+    }
+
+    #[test]
+    fn integer_round_trip() {
+        for n in values() {
+            assert_eq!(i64::try_from(&Snafu::from(n)), Ok(n), "{n}");
+            assert_eq!(
+                i128::try_from(&Snafu::from(n as i128)),
+                Ok(n as i128),
+                "{n}"
+            );
+        }
+    }
+
+    #[test]
+    fn i128_extremes() {
+        for n in [i128::MIN, i128::MIN + 1, i128::MAX - 1, i128::MAX] {
+            let snafu = Snafu::from(n);
+            assert_eq!(i128::try_from(&snafu), Ok(n), "{n}");
+            assert_eq!(snafu.to_string().parse::<Snafu>(), Ok(snafu), "{n}");
+        }
+    }
+
+    #[test]
+    fn string_round_trip() {
+        for n in values() {
+            let snafu = Snafu::from(n);
+            assert_eq!(snafu.to_string().parse::<Snafu>(), Ok(snafu), "{n}");
+        }
+    }
+
+    #[test]
+    fn addition_matches_integers() {
+        for (a, b) in values().zip(values().skip(7)) {
+            let expected = a as i128 + b as i128;
+            let sum = Snafu::from(a) + Snafu::from(b);
+            assert_eq!(i128::try_from(&sum), Ok(expected), "{a} + {b}");
+            assert_eq!(sum, Snafu::from(expected), "{a} + {b}");
+        }
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let max = Snafu::from(i64::MAX);
+        assert_eq!(i64::try_from(&(&max + &Snafu::from(1i64))), Err(()));
+        assert!("0".parse::<Snafu>().unwrap().digits.is_empty());
+        assert_eq!("".parse::<Snafu>(), Err(()));
+        assert_eq!("12a".parse::<Snafu>(), Err(()));
+    }
+}
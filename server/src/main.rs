@@ -1,6 +1,6 @@
 use axum::body::{boxed, Body};
 use axum::http::{Response, StatusCode};
-use axum::{response::IntoResponse, routing::get, Router};
+use axum::{response::IntoResponse, routing::get, Json, Router};
 use clap::Parser;
 use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::path::PathBuf;
@@ -76,6 +76,7 @@ async fn main() {
 
     let app = Router::new()
         .route("/api/hello", get(hello))
+        .route("/api/d02", get(d02_report))
         .merge(axum_extra::routing::SpaRouter::new(
             "/assets",
             opt.static_dir,
@@ -98,3 +99,15 @@ async fn main() {
 async fn hello() -> impl IntoResponse {
     "hello from server!"
 }
+
+async fn d02_report() -> impl IntoResponse {
+    let guide = match fs::read_to_string("d02/input.txt").await {
+        Ok(guide) => guide,
+        Err(_) => return (StatusCode::NOT_FOUND, "input file not found").into_response(),
+    };
+
+    match d02::analyse(&guide) {
+        Some(analysis) => Json(analysis).into_response(),
+        None => (StatusCode::UNPROCESSABLE_ENTITY, "invalid strategy guide").into_response(),
+    }
+}